@@ -1,22 +1,32 @@
-use crate::copy_client::{Author, ErrorInfo, LoginResult, MemberInfo};
-use crate::database::active::comic_view_log;
+use crate::copy_client::{Author, ErrorInfo, LoginResult, MemberInfo, RetryPolicy};
+use crate::database::active::{
+    author_work, comic_subscription, comic_view_log, followed_author, local_collect,
+};
 use crate::database::cache::{image_cache, web_cache};
 use crate::database::download::{
-    download_comic, download_comic_chapter, download_comic_group, download_comic_page,
+    download_comic, download_comic_chapter, download_comic_export, download_comic_group,
+    download_comic_page,
 };
-use crate::database::properties::property;
+use crate::database::properties::{property, secret};
 use crate::udto::{
-    UiCacheImage, UiChapterData, UiComicData, UiComicQuery, UiDownloadComic,
-    UiDownloadComicChapter, UiDownloadComicGroup, UiDownloadComicPage, UiLoginState,
-    UiPageCollectedComic, UiPageComicChapter, UiPageComicInExplore, UiPageRankItem,
-    UiPageUiComicInList, UiPageUiViewLog, UiQueryDownloadComic, UiRegisterResult, UiTags,
-    UiViewLog,
+    UiApiHostProbe, UiAuthorWork, UiCacheImage, UiChapterData, UiComicAllChapters, UiComicData,
+    UiComicQuery, UiComicSubscription, UiDownloadComic, UiDownloadComicChapter,
+    UiDownloadComicExport, UiDownloadComicGroup, UiDownloadComicPage, UiDownloadEvent,
+    UiFollowedAuthor, UiGroupChapters, UiLocalCollect, UiLogEntry, UiLoginState, UiPageBrowseComic,
+    UiPageCollectedComic, UiPageComicChapter, UiPageComicInExplore, UiPageComment, UiPageRankItem,
+    UiPageRoast, UiPageUiAuthorWork, UiPageUiComicInList, UiPageUiComicSubscription,
+    UiPageUiFollowedAuthor, UiPageUiLocalCollect, UiPageUiViewLog, UiQueryDownloadComic,
+    UiRegisterResult, UiRetryPolicy, UiRetryStats, UiTags, UiViewLog,
 };
 use crate::utils::{hash_lock, join_paths};
-use crate::{downloading, get_image_cache_dir, CLIENT};
+use crate::{
+    author_follow, browse_sync, downloading, get_image_cache_dir, offline, profile, subscription,
+    CLIENT,
+};
 use image::EncodableLayout;
 use napi_derive_ohos::napi;
-use napi_ohos::Result;
+use napi_ohos::threadsafe_function::ThreadsafeFunction;
+use napi_ohos::{Result, Status};
 use reqwest::Proxy;
 use std::future::Future;
 use std::time::Duration;
@@ -36,26 +46,61 @@ pub async fn init(root: String) {
 
 #[napi]
 pub async fn save_property(k: String, v: String) -> Result<()> {
-    property::save_property(k, v).await.map_err(map_anyhow)
+    block_on(property::save_property(k, v)).await
 }
 
 #[napi]
 pub async fn load_property(k: String) -> Result<String> {
-    property::load_property(k).await.map_err(map_anyhow)
+    block_on(property::load_property(k)).await
 }
 
 #[napi]
 pub async fn get_proxy() -> Result<String> {
-    property::load_property("proxy".to_owned())
-        .await
-        .map_err(map_anyhow)
+    block_on(property::load_property("proxy".to_owned())).await
+}
+
+// 抛给UI的错误以 [错误码] 开头, 错误码是固定的, UI根据错误码处理
+pub(crate) const ERROR_CODE_NETWORK: &str = "NETWORK";
+pub(crate) const ERROR_CODE_MESSAGE: &str = "MESSAGE";
+pub(crate) const ERROR_CODE_UNAUTHORIZED: &str = "UNAUTHORIZED";
+pub(crate) const ERROR_CODE_NOT_FOUND: &str = "NOT_FOUND";
+pub(crate) const ERROR_CODE_RATE_LIMITED: &str = "RATE_LIMITED";
+pub(crate) const ERROR_CODE_REGION_LOCKED: &str = "REGION_LOCKED";
+pub(crate) const ERROR_CODE_VIP_LOCKED: &str = "VIP_LOCKED";
+pub(crate) const ERROR_CODE_SERVER: &str = "SERVER_ERROR";
+pub(crate) const ERROR_CODE_INVALID_RESPONSE: &str = "INVALID_RESPONSE";
+pub(crate) const ERROR_CODE_OFFLINE: &str = "OFFLINE";
+pub(crate) const ERROR_CODE_UNKNOWN: &str = "UNKNOWN";
+
+pub(crate) fn error_code(info: &ErrorInfo) -> &'static str {
+    match info {
+        ErrorInfo::Network(_) => ERROR_CODE_NETWORK,
+        ErrorInfo::Message(_) => ERROR_CODE_MESSAGE,
+        ErrorInfo::Unauthorized(_) => ERROR_CODE_UNAUTHORIZED,
+        ErrorInfo::NotFound(_) => ERROR_CODE_NOT_FOUND,
+        ErrorInfo::RateLimited(_) => ERROR_CODE_RATE_LIMITED,
+        ErrorInfo::RegionLocked(_) => ERROR_CODE_REGION_LOCKED,
+        ErrorInfo::VipLocked(_) => ERROR_CODE_VIP_LOCKED,
+        ErrorInfo::Server(_, _) => ERROR_CODE_SERVER,
+        ErrorInfo::Convert(_) | ErrorInfo::InvalidResponse(_) => ERROR_CODE_INVALID_RESPONSE,
+        ErrorInfo::Offline => ERROR_CODE_OFFLINE,
+        ErrorInfo::Other(_) => ERROR_CODE_UNKNOWN,
+    }
+}
+
+pub(crate) fn map_error(e: anyhow::Error) -> napi_ohos::Error {
+    let reason = match e.downcast_ref::<crate::copy_client::Error>() {
+        Some(err) => format!("[{}] {}", error_code(&err.info), err.info),
+        None => format!("[{}] {:?}", ERROR_CODE_UNKNOWN, e),
+    };
+    napi_ohos::Error::new(Status::GenericFailure, reason)
 }
 
 async fn block_on<T>(f: impl Future<Output = anyhow::Result<T>>) -> napi_ohos::Result<T>
 where
     T: Send + Sync + 'static,
 {
-    f.await.map_err(map_anyhow)
+    f.await.map_err(map_error)
 }
 
 #[napi]
@@ -89,63 +134,186 @@ pub async fn get_api_host() -> Result<String> {
 pub async fn set_api_host(api: String) -> Result<()> {
     block_on(async {
         CLIENT.set_api_host(api.clone()).await;
-        property::save_property("api".to_owned(), api).await?;
+        property::save_property("api".to_owned(), api.clone()).await?;
+        let mut api_hosts = CLIENT.api_hosts().await.to_vec();
+        if !api_hosts.contains(&api) {
+            api_hosts.push(api);
+            save_api_hosts(api_hosts).await?;
+        }
         Ok(())
     })
     .await
 }
 
+async fn save_api_hosts(api_hosts: Vec<String>) -> anyhow::Result<()> {
+    property::save_property("api_hosts".to_owned(), serde_json::to_string(&api_hosts)?).await?;
+    CLIENT.set_api_hosts(api_hosts).await;
+    Ok(())
+}
+
+#[napi]
+pub async fn api_hosts() -> Result<Vec<String>> {
+    Ok(CLIENT.api_hosts().await.to_vec())
+}
+
+#[napi]
+pub async fn add_api_host(api: String) -> Result<()> {
+    block_on(async move {
+        let api = api.trim().trim_end_matches('/').to_owned();
+        url::Url::parse(api.as_str())?;
+        let mut api_hosts = CLIENT.api_hosts().await.to_vec();
+        if api_hosts.contains(&api) {
+            return Err(anyhow::anyhow!("api host already exists"));
+        }
+        api_hosts.push(api);
+        save_api_hosts(api_hosts).await
+    })
+    .await
+}
+
+#[napi]
+pub async fn remove_api_host(api: String) -> Result<()> {
+    block_on(async move {
+        let mut api_hosts = CLIENT.api_hosts().await.to_vec();
+        api_hosts.retain(|host| host != &api);
+        if api_hosts.is_empty() {
+            return Err(anyhow::anyhow!("at least one api host is required"));
+        }
+        // 删除正在使用的镜像时切换到第一个
+        if CLIENT.api_host_string().await.as_str() == api {
+            CLIENT.set_api_host(api_hosts[0].clone()).await;
+            property::save_property("api".to_owned(), api_hosts[0].clone()).await?;
+        }
+        save_api_hosts(api_hosts).await
+    })
+    .await
+}
+
+#[napi]
+pub async fn move_api_host(api: String, index: u32) -> Result<()> {
+    block_on(async move {
+        let mut api_hosts = CLIENT.api_hosts().await.to_vec();
+        let position = api_hosts
+            .iter()
+            .position(|host| host == &api)
+            .ok_or_else(|| anyhow::anyhow!("api host not found"))?;
+        let host = api_hosts.remove(position);
+        api_hosts.insert((index as usize).min(api_hosts.len()), host);
+        save_api_hosts(api_hosts).await
+    })
+    .await
+}
+
+#[napi]
+pub async fn probe_api_hosts() -> Result<Vec<UiApiHostProbe>> {
+    Ok(CLIENT
+        .probe_hosts()
+        .await
+        .into_iter()
+        .map(UiApiHostProbe::from)
+        .collect())
+}
+
 #[napi]
 pub async fn init_login_state() -> Result<UiLoginState> {
-    block_on(async {
-        let token = property::load_property("token".to_owned()).await?;
-        if token.is_empty() {
-            Ok(UiLoginState {
-                state: 0,
-                message: "".to_string(),
-                member: Default::default(),
-            })
-        } else {
-            CLIENT.set_token(token).await;
-            match CLIENT.member_info().await {
-                Ok(member) => Ok(UiLoginState {
+    block_on(load_login_state()).await
+}
+
+async fn load_login_state() -> anyhow::Result<UiLoginState> {
+    let token = secret::load_secret(profile::key("token").await).await?;
+    if token.is_empty() {
+        Ok(UiLoginState {
+            state: 0,
+            message: "".to_string(),
+            member: Default::default(),
+        })
+    } else {
+        crate::logging::add_secret(token.as_str());
+        CLIENT.set_token(token).await;
+        match CLIENT.member_info().await {
+            Ok(member) => {
+                spawn_sync_browse();
+                Ok(UiLoginState {
                     state: 1,
                     message: "".to_string(),
                     member: Some(member),
-                }),
-                Err(err) => {
-                    match err.info {
-                        ErrorInfo::Network(e) => Ok(UiLoginState {
-                            state: 2,
-                            message: e.to_string(),
-                            member: Default::default(),
-                        }),
-                        ErrorInfo::Message(_) => {
-                            // token 已经失效
-                            // todo : 用来token过期重新登录
-                            // property::load_property("username".to_owned()).await?;
-                            // property::load_property("password".to_owned()).await?;
-                            property::save_property("token".to_owned(), "".to_owned()).await?;
-                            Ok(UiLoginState {
-                                state: 0,
-                                message: "".to_string(),
-                                member: None,
-                            })
-                        }
-                        ErrorInfo::Convert(e) => Ok(UiLoginState {
-                            state: 2,
-                            message: e.to_string(),
-                            member: None,
-                        }),
-                        ErrorInfo::Other(e) => Ok(UiLoginState {
-                            state: 2,
-                            message: e.to_string(),
+                })
+            }
+            Err(err) => {
+                match err.info {
+                    ErrorInfo::Network(e) => Ok(UiLoginState {
+                        state: 2,
+                        message: e.to_string(),
+                        member: Default::default(),
+                    }),
+                    ErrorInfo::Unauthorized(_) => {
+                        // token 已经失效, 并且没有保存的用户名密码或者重新登录失败
+                        secret::save_secret(profile::key("token").await, "".to_owned()).await?;
+                        Ok(UiLoginState {
+                            state: 0,
+                            message: "".to_string(),
                             member: None,
-                        }),
+                        })
                     }
+                    info => Ok(UiLoginState {
+                        state: 2,
+                        message: info.to_string(),
+                        member: None,
+                    }),
                 }
             }
         }
+    }
+}
+
+// 退出当前账号, 删除保存的token和用户名密码以及账号相关的缓存
+// wipe_history为true时删除从账号同步来的浏览记录, 本地阅读的记录保留
+#[napi]
+pub async fn logout(wipe_history: bool) -> Result<()> {
+    block_on(async move {
+        CLIENT.set_credentials("", "").await;
+        CLIENT.set_token("").await;
+        secret::delete_secret(profile::key("token").await).await?;
+        secret::delete_secret(profile::key("password").await).await?;
+        property::delete_property(profile::key("username").await).await?;
+        web_cache::clean_web_cache_by_prefix(profile::cache_key("COMIC_COLLECT").await.as_str())
+            .await?;
+        web_cache::clean_web_cache_by_prefix(profile::cache_key("COMIC_QUERY$").await.as_str())
+            .await?;
+        browse_sync::reset(wipe_history).await
+    })
+    .await
+}
+
+#[napi]
+pub async fn profiles() -> Result<Vec<String>> {
+    block_on(profile::list()).await
+}
+
+#[napi]
+pub async fn active_profile() -> String {
+    profile::active().await
+}
+
+#[napi]
+pub async fn add_profile(name: String) -> Result<()> {
+    block_on(profile::add(name)).await
+}
+
+#[napi]
+pub async fn remove_profile(name: String) -> Result<()> {
+    block_on(profile::remove(name)).await
+}
+
+// 切换账号, 使用该账号的token和设备标识重新设置CLIENT
+#[napi]
+pub async fn switch_profile(name: String) -> Result<UiLoginState> {
+    block_on(async move {
+        profile::set_active(name).await?;
+        CLIENT.set_token("").await;
+        crate::init_device().await;
+        crate::init_credentials().await;
+        load_login_state().await
     })
     .await
 }
@@ -156,11 +324,18 @@ pub async fn login(username: String, password: String) -> Result<UiLoginState> {
         let result = CLIENT.login(username.as_str(), password.as_str()).await;
         match result {
             Ok(ok) => {
+                crate::logging::add_secret(ok.token.as_str());
+                crate::logging::add_secret(password.as_str());
                 CLIENT.set_token(ok.token.clone()).await;
-                property::save_property("token".to_owned(), ok.token.clone()).await?;
-                property::save_property("username".to_owned(), username).await?;
-                property::save_property("password".to_owned(), password).await?;
-                let _ = web_cache::clean_web_cache_by_like(format!("COMIC_QUERY$%").as_str()).await;
+                secret::save_secret(profile::key("token").await, ok.token.clone()).await?;
+                property::save_property(profile::key("username").await, username.clone()).await?;
+                secret::save_secret(profile::key("password").await, password.clone()).await?;
+                CLIENT.set_credentials(username, password).await;
+                let _ = web_cache::clean_web_cache_by_prefix(
+                    profile::cache_key("COMIC_QUERY$").await.as_str(),
+                )
+                .await;
+                spawn_sync_browse();
                 Ok(UiLoginState {
                     state: 1,
                     message: "".to_string(),
@@ -178,14 +353,9 @@ pub async fn login(username: String, password: String) -> Result<UiLoginState> {
                     message: err,
                     member: None,
                 }),
-                ErrorInfo::Convert(err) => Ok(UiLoginState {
+                info => Ok(UiLoginState {
                     state: 2,
-                    message: err.to_string(),
-                    member: None,
-                }),
-                ErrorInfo::Other(err) => Ok(UiLoginState {
-                    state: 2,
-                    message: err.to_string(),
+                    message: info.to_string(),
                     member: None,
                 }),
             },
@@ -194,7 +364,7 @@ pub async fn login(username: String, password: String) -> Result<UiLoginState> {
     .await
 }
 
-fn member_from_result(result: LoginResult) -> MemberInfo {
+pub(crate) fn member_from_result(result: LoginResult) -> MemberInfo {
     MemberInfo {
         user_id: result.user_id,
         username: result.username,
@@ -248,14 +418,9 @@ pub async fn register(username: String, password: String) -> Result<UiRegisterRe
                     message: err,
                     member: None,
                 }),
-                ErrorInfo::Convert(err) => Ok(UiRegisterResult {
+                info => Ok(UiRegisterResult {
                     state: 2,
-                    message: err.to_string(),
-                    member: None,
-                }),
-                ErrorInfo::Other(err) => Ok(UiRegisterResult {
-                    state: 2,
-                    message: err.to_string(),
+                    message: info.to_string(),
                     member: None,
                 }),
             },
@@ -267,15 +432,22 @@ pub async fn register(username: String, password: String) -> Result<UiRegisterRe
 #[napi]
 pub async fn rank(date_type: String, offset: i64, limit: i64) -> Result<UiPageRankItem> {
     let key = format!("COMIC_RANK${}${}${}", date_type, offset, limit);
-    block_on(web_cache::cache_first_map(
-        key,
-        Duration::from_secs(60 * 60 * 2),
-        Box::pin(async move {
-            CLIENT
-                .comic_rank(date_type.as_str(), offset as u64, limit as u64)
-                .await
-        }),
-    ))
+    block_on(async move {
+        let cached = web_cache::cache_map_with_policy::<_, UiPageRankItem>(
+            key,
+            Duration::from_secs(60 * 60 * 2),
+            Box::pin(async move {
+                CLIENT
+                    .comic_rank(date_type.as_str(), offset as u64, limit as u64)
+                    .await
+            }),
+        )
+        .await?;
+        Ok(UiPageRankItem {
+            stale: cached.stale,
+            ..cached.value
+        })
+    })
     .await
 }
 
@@ -292,14 +464,46 @@ pub async fn recommends(offset: i64, limit: i64) -> Result<UiPageUiComicInList>
 
 #[napi]
 pub async fn comic(path_word: String) -> Result<UiComicData> {
+    block_on(or_downloaded(
+        load_comic(path_word.clone()),
+        offline::comic_data(path_word.as_str()),
+    ))
+    .await
+}
+
+async fn load_comic(path_word: String) -> anyhow::Result<UiComicData> {
     let key = format!("COMIC${}", path_word);
-    web_cache::cache_first_map(
+    let cached = web_cache::cache_map_with_policy::<_, UiComicData>(
         key,
         Duration::from_secs(60 * 60 * 2),
         Box::pin(async move { CLIENT.comic(path_word.as_str()).await }),
     )
-    .await
-    .map_err(map_anyhow)
+    .await?;
+    Ok(UiComicData {
+        stale: cached.stale,
+        ..cached.value
+    })
+}
+
+// 离线模式, 或者网络和缓存都读取失败时, 才使用下载的数据
+// 下载的可能只是部分章节, 不替换正常的网络结果
+async fn or_downloaded<T>(
+    online: impl Future<Output = anyhow::Result<T>>,
+    downloaded: impl Future<Output = anyhow::Result<Option<T>>>,
+) -> anyhow::Result<T> {
+    if CLIENT.is_offline() {
+        if let Some(value) = downloaded.await? {
+            return Ok(value);
+        }
+        return online.await;
+    }
+    match online.await {
+        Ok(value) => Ok(value),
+        Err(err) => match downloaded.await {
+            Ok(Some(value)) => Ok(value),
+            _ => Err(err),
+        },
+    }
 }
 
 #[napi]
@@ -310,26 +514,85 @@ pub async fn comic_chapters(
     offset: i64,
 ) -> Result<UiPageComicChapter> {
     let key = format!("COMIC_CHAPTERS${comic_path_word}${group_path_word}${limit}${offset}");
-    block_on(web_cache::cache_first_map(
+    block_on(or_downloaded(
+        async {
+            let comic_path_word = comic_path_word.clone();
+            let group_path_word = group_path_word.clone();
+            let cached = web_cache::cache_map_with_policy::<_, UiPageComicChapter>(
+                key,
+                Duration::from_secs(60 * 60 * 2),
+                Box::pin(async move {
+                    CLIENT
+                        .comic_chapter(
+                            comic_path_word.as_str(),
+                            group_path_word.as_str(),
+                            limit as u64,
+                            offset as u64,
+                        )
+                        .await
+                }),
+            )
+            .await?;
+            Ok(UiPageComicChapter {
+                stale: cached.stale,
+                ..cached.value
+            })
+        },
+        offline::comic_chapters(
+            comic_path_word.as_str(),
+            group_path_word.as_str(),
+            limit,
+            offset,
+        ),
+    ))
+    .await
+}
+
+const ALL_CHAPTERS_EXPIRE: Duration = Duration::from_secs(60 * 60 * 2);
+
+// 所有分组的全部章节, 漫画的最新章节变化时重新读取
+#[napi]
+pub async fn comic_all_chapters(comic_path_word: String) -> Result<UiComicAllChapters> {
+    block_on(async move {
+        let key = format!("COMIC_ALL_CHAPTERS${comic_path_word}");
+        // 章节列表没有过期时不读取漫画详情, 漫画更新时由收藏的检查清除
+        if let Some(all) = web_cache::load_fresh(key.as_str(), ALL_CHAPTERS_EXPIRE).await? {
+            return Ok(all);
+        }
+        let comic = load_comic(comic_path_word.clone()).await?;
+        let all = load_all_chapters(key.clone(), comic.clone()).await?;
+        // 离线时无法重新读取, 不清理缓存
+        if all.last_chapter_uuid == comic.comic.last_chapter.uuid || CLIENT.is_offline() {
+            return Ok(all);
+        }
+        web_cache::clean_web_cache_by_key(format!("COMIC${comic_path_word}").as_str()).await?;
+        web_cache::clean_web_cache_by_key(key.as_str()).await?;
+        let comic = load_comic(comic_path_word).await?;
+        load_all_chapters(key, comic).await
+    })
+    .await
+}
+
+async fn load_all_chapters(key: String, comic: UiComicData) -> anyhow::Result<UiComicAllChapters> {
+    web_cache::cache_first(
         key,
-        Duration::from_secs(60 * 60 * 2),
+        ALL_CHAPTERS_EXPIRE,
         Box::pin(async move {
-            CLIENT
-                .comic_chapter(
-                    comic_path_word.as_str(),
-                    group_path_word.as_str(),
-                    limit as u64,
-                    offset as u64,
-                )
-                .await
+            let groups = CLIENT
+                .comic_all_chapters(comic.comic.path_word.as_str(), comic.groups)
+                .await?;
+            Ok(UiComicAllChapters {
+                last_chapter_uuid: comic.comic.last_chapter.uuid,
+                groups: groups.into_iter().map(UiGroupChapters::from).collect(),
+            })
         }),
-    ))
+    )
     .await
 }
 
 #[napi]
 pub async fn comic_query(path_word: String) -> Result<UiComicQuery> {
-    let key = format!("COMIC_QUERY${path_word}");
+    let key = profile::cache_key(format!("COMIC_QUERY${path_word}").as_str()).await;
     block_on(web_cache::cache_first_map(
         key,
         Duration::from_secs(60 * 60 * 2),
@@ -344,18 +607,80 @@ pub async fn comic_chapter_data(
     chapter_uuid: String,
 ) -> Result<UiChapterData> {
     let key = format!("COMIC_CHAPTER_DATA${comic_path_word}${chapter_uuid}");
+    block_on(or_downloaded(
+        async {
+            let comic_path_word = comic_path_word.clone();
+            let chapter_uuid = chapter_uuid.clone();
+            web_cache::cache_first_map(
+                key,
+                Duration::from_secs(60 * 60 * 2),
+                Box::pin(async move {
+                    CLIENT
+                        .comic_chapter_data(comic_path_word.as_str(), chapter_uuid.as_str())
+                        .await
+                }),
+            )
+            .await
+        },
+        offline::chapter_data(comic_path_word.as_str(), chapter_uuid.as_str()),
+    ))
+    .await
+}
+
+#[napi]
+pub async fn comments(
+    comic_id: String,
+    reply_id: Option<String>,
+    offset: i64,
+    limit: i64,
+) -> Result<UiPageComment> {
+    let key = format!("COMIC_COMMENTS${comic_id}${reply_id:?}${offset}${limit}");
     block_on(web_cache::cache_first_map(
         key,
-        Duration::from_secs(60 * 60 * 2),
+        Duration::from_secs(60 * 10),
+        Box::pin(async move {
+            CLIENT
+                .comments(
+                    comic_id.as_str(),
+                    reply_id.as_deref(),
+                    offset as u64,
+                    limit as u64,
+                )
+                .await
+        }),
+    ))
+    .await
+}
+
+#[napi]
+pub async fn roasts(chapter_id: String, offset: i64, limit: i64) -> Result<UiPageRoast> {
+    let key = format!("COMIC_ROASTS${chapter_id}${offset}${limit}");
+    block_on(web_cache::cache_first_map(
+        key,
+        Duration::from_secs(60 * 10),
         Box::pin(async move {
             CLIENT
-                .comic_chapter_data(comic_path_word.as_str(), chapter_uuid.as_str())
+                .roasts(chapter_id.as_str(), offset as u64, limit as u64)
                 .await
         }),
     ))
     .await
 }
 
+#[napi]
+pub async fn comment(comic_id: String, comment: String, reply_id: Option<String>) -> Result<()> {
+    block_on(async move {
+        CLIENT
+            .comment(comic_id.as_str(), comment.as_str(), reply_id.as_deref())
+            .await?;
+        // 回复会改变父评论的回复数, 所以清理这部漫画的全部评论缓存
+        web_cache::clean_web_cache_by_prefix(format!("COMIC_COMMENTS${comic_id}$").as_str())
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
 #[napi]
 pub async fn tags() -> Result<UiTags> {
     let key = format!("COMIC_TAGS");
@@ -379,24 +704,168 @@ pub async fn explorer(
         "COMIC_EXPLORER${:?}${:?}${:?}${}${}",
         ordering, top, theme, limit, offset
     );
-    block_on(web_cache::cache_first_map(
-        key,
-        Duration::from_secs(60 * 60 * 2),
-        Box::pin(async move {
-            CLIENT
-                .explore(
-                    ordering.as_deref(),
-                    top.as_deref(),
-                    theme.as_deref(),
-                    offset as u64,
-                    limit as u64,
-                )
-                .await
-        }),
-    ))
+    block_on(async move {
+        let cached = web_cache::cache_map_with_policy::<_, UiPageComicInExplore>(
+            key,
+            Duration::from_secs(60 * 60 * 2),
+            Box::pin(async move {
+                CLIENT
+                    .explore(
+                        ordering.as_deref(),
+                        top.as_deref(),
+                        theme.as_deref(),
+                        offset as u64,
+                        limit as u64,
+                    )
+                    .await
+            }),
+        )
+        .await?;
+        Ok(UiPageComicInExplore {
+            stale: cached.stale,
+            ..cached.value
+        })
+    })
+    .await
+}
+
+#[napi]
+pub async fn explore_by_author(
+    author: String,
+    ordering: Option<String>,
+    offset: i64,
+    limit: i64,
+) -> Result<UiPageComicInExplore> {
+    let key = format!(
+        "COMIC_EXPLORE_BY_AUTHOR${}${:?}${}${}",
+        author, ordering, limit, offset
+    );
+    block_on(async move {
+        let cached = web_cache::cache_map_with_policy::<_, UiPageComicInExplore>(
+            key,
+            Duration::from_secs(60 * 60 * 2),
+            Box::pin(async move {
+                CLIENT
+                    .explore_by_author(
+                        author.as_str(),
+                        ordering.as_deref(),
+                        offset as u64,
+                        limit as u64,
+                    )
+                    .await
+            }),
+        )
+        .await?;
+        Ok(UiPageComicInExplore {
+            stale: cached.stale,
+            ..cached.value
+        })
+    })
+    .await
+}
+
+#[napi]
+pub async fn explore_by_author_name(
+    author_name: String,
+    ordering: Option<String>,
+    offset: i64,
+    limit: i64,
+) -> Result<UiPageComicInExplore> {
+    let key = format!(
+        "COMIC_EXPLORE_BY_AUTHOR_NAME${}${:?}${}${}",
+        author_name, ordering, limit, offset
+    );
+    block_on(async move {
+        let cached = web_cache::cache_map_with_policy::<_, UiPageComicInExplore>(
+            key,
+            Duration::from_secs(60 * 60 * 2),
+            Box::pin(async move {
+                CLIENT
+                    .explore_by_author_name(
+                        author_name.as_str(),
+                        ordering.as_deref(),
+                        offset as u64,
+                        limit as u64,
+                    )
+                    .await
+            }),
+        )
+        .await?;
+        Ok(UiPageComicInExplore {
+            stale: cached.stale,
+            ..cached.value
+        })
+    })
+    .await
+}
+
+// 关注作者, 下次检查时记录作者已有的作品
+#[napi]
+pub async fn follow_author(author_path_word: String, author_name: String) -> Result<()> {
+    block_on(followed_author::follow(author_path_word, author_name)).await
+}
+
+#[napi]
+pub async fn unfollow_author(author_path_word: String) -> Result<()> {
+    block_on(async move {
+        followed_author::delete_by_author_path_word(author_path_word.clone()).await?;
+        author_work::delete_by_author_path_word(author_path_word).await
+    })
     .await
 }
 
+#[napi]
+pub async fn find_followed_author(author_path_word: String) -> Result<Option<UiFollowedAuthor>> {
+    block_on(async move {
+        Ok(followed_author::find_by_author_path_word(author_path_word)
+            .await?
+            .map(UiFollowedAuthor::from))
+    })
+    .await
+}
+
+#[napi]
+pub async fn list_followed_authors(offset: i64, limit: i64) -> Result<UiPageUiFollowedAuthor> {
+    block_on(async move {
+        let count = followed_author::count().await?;
+        let list = followed_author::load_followed_authors(offset as u64, limit as u64).await?;
+        Ok(UiPageUiFollowedAuthor {
+            total: count as i64,
+            limit,
+            offset,
+            list: list.into_iter().map(UiFollowedAuthor::from).collect(),
+        })
+    })
+    .await
+}
+
+// 返回新发现的作品数量
+#[napi]
+pub async fn check_followed_authors() -> Result<i64> {
+    block_on(async move { Ok(author_follow::check_followed_authors().await? as i64) }).await
+}
+
+#[napi]
+pub async fn author_feed(only_unseen: bool, offset: i64, limit: i64) -> Result<UiPageUiAuthorWork> {
+    block_on(async move {
+        let count = author_work::feed_count(only_unseen).await?;
+        let list = author_work::load_feed(only_unseen, offset as u64, limit as u64).await?;
+        Ok(UiPageUiAuthorWork {
+            total: count as i64,
+            limit,
+            offset,
+            list: list.into_iter().map(UiAuthorWork::from).collect(),
+        })
+    })
+    .await
+}
+
+// comic_path_words为空时全部标记为已读
+#[napi]
+pub async fn mark_author_feed_seen(comic_path_words: Vec<String>) -> Result<()> {
+    block_on(author_work::mark_seen(comic_path_words)).await
+}
+
 #[napi]
 pub async fn comic_search(
     q_type: String,
@@ -424,15 +893,17 @@ pub async fn view_comic_info(
     comic_authors: Vec<Author>,
     comic_cover: String,
 ) -> Result<()> {
-    Ok(comic_view_log::view_info(comic_view_log::Model {
-        comic_path_word,
-        comic_name,
-        comic_authors: serde_json::to_string(&comic_authors).map_err(map_anyhow)?,
-        comic_cover,
-        ..Default::default()
+    block_on(async move {
+        comic_view_log::view_info(comic_view_log::Model {
+            comic_path_word,
+            comic_name,
+            comic_authors: serde_json::to_string(&comic_authors)?,
+            comic_cover,
+            ..Default::default()
+        })
+        .await
     })
     .await
-    .map_err(map_anyhow)?)
 }
 
 #[napi]
@@ -445,7 +916,7 @@ pub async fn view_chapter_page(
     chapter_count: i64,
     page_rank: i32,
 ) -> Result<()> {
-    comic_view_log::view_page(comic_view_log::Model {
+    block_on(comic_view_log::view_page(comic_view_log::Model {
         comic_path_word,
         chapter_uuid,
         chapter_name,
@@ -454,9 +925,8 @@ pub async fn view_chapter_page(
         chapter_count,
         page_rank,
         ..Default::default()
-    })
+    }))
     .await
-    .map_err(map_anyhow)
 }
 
 #[napi]
@@ -488,6 +958,29 @@ pub async fn list_comic_view_logs(offset: i64, limit: i64) -> Result<UiPageUiVie
     .await
 }
 
+fn spawn_sync_browse() {
+    tokio::spawn(async {
+        if let Err(e) = browse_sync::sync_browse().await {
+            log::warn!("sync_browse error: {:?}", e);
+        }
+    });
+}
+
+#[napi]
+pub async fn sync_browse_history() -> Result<i64> {
+    block_on(async move { Ok(browse_sync::sync_browse().await? as i64) }).await
+}
+
+#[napi]
+pub async fn browse_history(offset: i64, limit: i64) -> Result<UiPageBrowseComic> {
+    block_on(async move {
+        Ok(UiPageBrowseComic::from(
+            CLIENT.browser(offset as u64, limit as u64).await?,
+        ))
+    })
+    .await
+}
+
 #[napi]
 pub async fn collect_to_account(
     comic_id: String,
@@ -505,13 +998,17 @@ async fn collect_to_account_move(
     CLIENT
         .collect(comic_id.as_str(), is_collect)
         .await
-        .map_err(map_anyhow)?;
-    web_cache::clean_web_cache_by_like("COMIC_COLLECT%")
+        .map_err(|e| map_error(e.into()))?;
+    web_cache::clean_web_cache_by_prefix(profile::cache_key("COMIC_COLLECT").await.as_str())
         .await
-        .map_err(map_anyhow)?;
-    web_cache::clean_web_cache_by_like(format!("COMIC_QUERY${comic_path_word}").as_str())
-        .await
-        .map_err(map_anyhow)?;
+        .map_err(map_error)?;
+    web_cache::clean_web_cache_by_key(
+        profile::cache_key(format!("COMIC_QUERY${comic_path_word}").as_str())
+            .await
+            .as_str(),
+    )
+    .await
+    .map_err(map_error)?;
     Ok(())
 }
 
@@ -522,7 +1019,10 @@ pub async fn collect_from_account(
     offset: i64,
     limit: i64,
 ) -> Result<UiPageCollectedComic> {
-    let key = format!("COMIC_COLLECT${free_type}${ordering}${offset}${limit}$");
+    let key = profile::cache_key(
+        format!("COMIC_COLLECT${free_type}${ordering}${offset}${limit}$").as_str(),
+    )
+    .await;
     block_on(web_cache::cache_first_map(
         key,
         Duration::from_secs(60 * 60 * 2),
@@ -535,6 +1035,105 @@ pub async fn collect_from_account(
     .await
 }
 
+// 重新查询本地收藏和账号收藏的漫画, 返回有新章节的漫画数量
+#[napi]
+pub async fn refresh_subscriptions() -> Result<i64> {
+    block_on(async move { Ok(subscription::refresh_subscriptions().await? as i64) }).await
+}
+
+#[napi]
+pub async fn list_comic_updates(offset: i64, limit: i64) -> Result<UiPageUiComicSubscription> {
+    block_on(async move {
+        let profile = profile::active().await;
+        let count = comic_subscription::updates_count(profile.as_str()).await?;
+        let list =
+            comic_subscription::load_updates(profile.as_str(), offset as u64, limit as u64).await?;
+        Ok(UiPageUiComicSubscription {
+            total: count as i64,
+            limit,
+            offset,
+            list: list.into_iter().map(UiComicSubscription::from).collect(),
+        })
+    })
+    .await
+}
+
+// comic_path_words为空时清除全部
+#[napi]
+pub async fn clear_comic_updates(comic_path_words: Vec<String>) -> Result<()> {
+    block_on(async move {
+        comic_subscription::clear_updates(profile::active().await.as_str(), comic_path_words).await
+    })
+    .await
+}
+
+#[napi]
+pub async fn add_local_collect(data: UiLocalCollect) -> Result<()> {
+    block_on(local_collect::save(local_collect::Model::from(data))).await
+}
+
+#[napi]
+pub async fn remove_local_collect(path_word: String) -> Result<()> {
+    block_on(local_collect::delete_by_path_word(path_word)).await
+}
+
+#[napi]
+pub async fn find_local_collect(path_word: String) -> Result<Option<UiLocalCollect>> {
+    block_on(async move {
+        Ok(local_collect::find_by_path_word(path_word)
+            .await?
+            .map(UiLocalCollect::from))
+    })
+    .await
+}
+
+#[napi]
+pub async fn list_local_collects(
+    ordering: String,
+    offset: i64,
+    limit: i64,
+) -> Result<UiPageUiLocalCollect> {
+    block_on(async move {
+        let count = local_collect::count().await?;
+        let list =
+            local_collect::load_local_collects(ordering.as_str(), offset as u64, limit as u64)
+                .await?;
+        Ok(UiPageUiLocalCollect {
+            total: count as i64,
+            limit,
+            offset,
+            list: list.into_iter().map(UiLocalCollect::from).collect(),
+        })
+    })
+    .await
+}
+
+#[napi]
+pub async fn search_local_collects(
+    keyword: String,
+    ordering: String,
+    offset: i64,
+    limit: i64,
+) -> Result<UiPageUiLocalCollect> {
+    block_on(async move {
+        let count = local_collect::search_count(keyword.as_str()).await?;
+        let list = local_collect::search(
+            keyword.as_str(),
+            ordering.as_str(),
+            offset as u64,
+            limit as u64,
+        )
+        .await?;
+        Ok(UiPageUiLocalCollect {
+            total: count as i64,
+            limit,
+            offset,
+            list: list.into_iter().map(UiLocalCollect::from).collect(),
+        })
+    })
+    .await
+}
+
 #[napi]
 pub async fn cache_image(
     cache_key: String,
@@ -654,9 +1253,10 @@ pub async fn download_comics() -> Result<Vec<UiDownloadComic>> {
 
 #[napi]
 pub async fn download_comic_groups(comic_path_word: String) -> Result<Vec<UiDownloadComicGroup>> {
-    let coll = download_comic_group::find_by_comic_path_word(comic_path_word.as_str())
-        .await
-        .map_err(map_anyhow)?;
+    let coll = block_on(download_comic_group::find_by_comic_path_word(
+        comic_path_word.as_str(),
+    ))
+    .await?;
     Ok(coll.into_iter().map(UiDownloadComicGroup::from).collect())
 }
 
@@ -700,12 +1300,177 @@ pub async fn download_set_pause(pause: bool) -> Result<()> {
     Ok(downloading::download_set_pause(pause).await)
 }
 
+#[napi]
+pub fn set_auth_listener(
+    callback: ThreadsafeFunction<UiLoginState, (), UiLoginState, Status, false>,
+) {
+    crate::auth_event::set_listener(Some(callback));
+}
+
+#[napi]
+pub fn remove_auth_listener() {
+    crate::auth_event::set_listener(None);
+}
+
+#[napi]
+pub fn set_download_listener(
+    callback: ThreadsafeFunction<UiDownloadEvent, (), UiDownloadEvent, Status, false>,
+) {
+    crate::download_event::set_listener(Some(callback));
+}
+
+#[napi]
+pub fn remove_download_listener() {
+    crate::download_event::set_listener(None);
+}
+
+#[napi]
+pub async fn download_thread() -> Result<i32> {
+    Ok(downloading::download_thread().await)
+}
+
+#[napi]
+pub async fn set_download_thread(thread: i32) -> Result<()> {
+    block_on(downloading::set_download_thread(thread)).await
+}
+
+#[napi]
+pub async fn download_rate_limit() -> Result<f64> {
+    Ok(downloading::download_rate_limit().await)
+}
+
+#[napi]
+pub async fn set_download_rate_limit(rate: f64) -> Result<()> {
+    block_on(downloading::set_download_rate_limit(rate)).await
+}
+
+#[napi]
+pub async fn retry_policy() -> Result<UiRetryPolicy> {
+    Ok(UiRetryPolicy::from(CLIENT.retry_policy().await.as_ref()))
+}
+
+#[napi]
+pub async fn set_retry_policy(policy: UiRetryPolicy) -> Result<()> {
+    block_on(async move {
+        if !(1..=10).contains(&policy.max_attempts) {
+            return Err(anyhow::anyhow!("max attempts must be in 1..=10"));
+        }
+        if policy.base_delay_ms < 0 || policy.max_delay_ms < policy.base_delay_ms {
+            return Err(anyhow::anyhow!("retry delay must be 0 <= base <= max"));
+        }
+        property::save_property(
+            "retry_max_attempts".to_owned(),
+            policy.max_attempts.to_string(),
+        )
+        .await?;
+        property::save_property(
+            "retry_base_delay_ms".to_owned(),
+            policy.base_delay_ms.to_string(),
+        )
+        .await?;
+        property::save_property(
+            "retry_max_delay_ms".to_owned(),
+            policy.max_delay_ms.to_string(),
+        )
+        .await?;
+        CLIENT
+            .set_retry_policy(RetryPolicy {
+                max_attempts: policy.max_attempts as u32,
+                base_delay: Duration::from_millis(policy.base_delay_ms as u64),
+                max_delay: Duration::from_millis(policy.max_delay_ms as u64),
+            })
+            .await;
+        Ok(())
+    })
+    .await
+}
+
+#[napi]
+pub fn retry_stats() -> UiRetryStats {
+    UiRetryStats::from(CLIENT.retry_stats())
+}
+
+#[napi]
+pub fn fetch_logs(min_level: Option<String>) -> Result<Vec<UiLogEntry>> {
+    let min_level = match min_level {
+        Some(level) => Some(
+            level
+                .parse::<log::Level>()
+                .map_err(|_| map_error(anyhow::anyhow!("unknown log level")))?,
+        ),
+        None => None,
+    };
+    Ok(crate::logging::fetch(min_level)
+        .into_iter()
+        .map(UiLogEntry::from)
+        .collect())
+}
+
+#[napi]
+pub fn clear_logs() -> Result<()> {
+    crate::logging::clear().map_err(|e| map_error(e.into()))
+}
+
+#[napi]
+pub fn log_level() -> String {
+    log::max_level().to_string()
+}
+
+#[napi]
+pub async fn set_log_level(level: String) -> Result<()> {
+    block_on(async move {
+        let filter = crate::logging::parse_level(level.as_str())
+            .ok_or_else(|| anyhow::anyhow!("unknown log level"))?;
+        property::save_property("log_level".to_owned(), filter.to_string()).await?;
+        crate::logging::set_level(filter);
+        Ok(())
+    })
+    .await
+}
+
+#[napi]
+pub fn offline_mode() -> bool {
+    CLIENT.is_offline()
+}
+
+// 离线模式下只使用缓存和下载的内容
+#[napi]
+pub async fn set_offline_mode(offline: bool) -> Result<()> {
+    block_on(async move {
+        property::save_property("offline_mode".to_owned(), offline.to_string()).await?;
+        CLIENT.set_offline(offline);
+        Ok(())
+    })
+    .await
+}
+
+// fresh_only / stale_while_revalidate / stale_if_error
+#[napi]
+pub async fn cache_policy() -> String {
+    web_cache::CACHE_POLICY.lock().await.as_str().to_owned()
+}
+
+#[napi]
+pub async fn set_cache_policy(policy: String) -> Result<()> {
+    block_on(async move {
+        let parsed = web_cache::CachePolicy::parse(policy.as_str())
+            .ok_or_else(|| anyhow::anyhow!("unknown cache policy"))?;
+        property::save_property("cache_policy".to_owned(), policy).await?;
+        *web_cache::CACHE_POLICY.lock().await = parsed;
+        Ok(())
+    })
+    .await
+}
+
 #[napi]
 pub async fn http_get(url: String) -> Result<String> {
     block_on(http_get_inner(url)).await
 }
 
 async fn http_get_inner(url: String) -> anyhow::Result<String> {
+    if CLIENT.is_offline() {
+        return Err(crate::copy_client::Error::from_info(ErrorInfo::Offline).into());
+    }
     Ok(reqwest::ClientBuilder::new()
         .user_agent("kobi")
         .build()?
@@ -780,3 +1545,39 @@ pub async fn exports(
     ))
     .await
 }
+
+#[napi]
+pub async fn imports(paths: Vec<String>) -> Result<Vec<String>> {
+    block_on(crate::imports::imports(paths)).await
+}
+
+#[napi]
+pub async fn download_and_export_to() -> Result<String> {
+    Ok(downloading::download_and_export_to().await)
+}
+
+#[napi]
+pub async fn set_download_and_export_to(path: String) -> Result<()> {
+    block_on(downloading::set_download_and_export_to(path)).await
+}
+
+#[napi]
+pub async fn download_and_export_type() -> Result<String> {
+    Ok(downloading::download_and_export_type().await)
+}
+
+#[napi]
+pub async fn set_download_and_export_type(exports_type: String) -> Result<()> {
+    block_on(downloading::set_download_and_export_type(exports_type)).await
+}
+
+#[napi]
+pub async fn download_comic_exports(comic_path_word: String) -> Result<Vec<UiDownloadComicExport>> {
+    Ok(block_on(download_comic_export::find_by_comic_path_word(
+        comic_path_word.as_str(),
+    ))
+    .await?
+    .into_iter()
+    .map(UiDownloadComicExport::from)
+    .collect())
+}
@@ -0,0 +1,55 @@
+use crate::api::api::member_from_result;
+use crate::copy_client::AuthChange;
+use crate::database::properties::secret;
+use crate::udto::UiLoginState;
+use lazy_static::lazy_static;
+use napi_ohos::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi_ohos::Status;
+use std::sync::Arc;
+
+pub(crate) type AuthListener = ThreadsafeFunction<UiLoginState, (), UiLoginState, Status, false>;
+
+lazy_static! {
+    static ref LISTENER: std::sync::Mutex<Option<Arc<AuthListener>>> = std::sync::Mutex::new(None);
+}
+
+pub(crate) fn set_listener(listener: Option<AuthListener>) {
+    *LISTENER.lock().unwrap() = listener.map(Arc::new);
+}
+
+pub(crate) fn emit(state: UiLoginState) {
+    let listener = LISTENER.lock().unwrap().clone();
+    if let Some(listener) = listener {
+        listener.call(state, ThreadsafeFunctionCallMode::NonBlocking);
+    }
+}
+
+// CLIENT自动重新登录后保存新的token, 失败时清除token, 并通知UI
+// token_key在设置凭据时确定, 保存时切换了账号也写入原来的账号
+pub(crate) fn on_auth_change(token_key: String, change: AuthChange) {
+    let (token, state) = match change {
+        AuthChange::Relogin(result) => {
+            crate::logging::add_secret(result.token.as_str());
+            (
+                result.token.clone(),
+                UiLoginState {
+                    state: 1,
+                    message: "".to_string(),
+                    member: Some(member_from_result(*result)),
+                },
+            )
+        }
+        AuthChange::ReloginFailed(message) => (
+            "".to_owned(),
+            UiLoginState {
+                state: 0,
+                message,
+                member: None,
+            },
+        ),
+    };
+    tokio::spawn(async move {
+        let _ = secret::save_secret(token_key, token).await;
+    });
+    emit(state);
+}
@@ -0,0 +1,86 @@
+use crate::copy_client::ComicInExplore;
+use crate::database::active::{author_work, followed_author};
+use crate::CLIENT;
+use lazy_static::lazy_static;
+use std::collections::HashSet;
+use tokio::sync::Mutex;
+
+const PAGE_SIZE: u64 = 50;
+
+lazy_static! {
+    static ref CHECK_LOCK: Mutex<()> = Mutex::new(());
+}
+
+// 检查关注的作者是否有新作品, 返回发现的新作品数量
+pub(crate) async fn check_followed_authors() -> anyhow::Result<u64> {
+    let _lock = CHECK_LOCK.lock().await;
+    let mut found = 0;
+    for author in followed_author::load_all().await? {
+        match check_author(&author).await {
+            Ok(count) => found += count,
+            Err(e) => log::warn!("check author {} error: {:?}", author.author_path_word, e),
+        }
+    }
+    Ok(found)
+}
+
+async fn check_author(author: &followed_author::Model) -> anyhow::Result<u64> {
+    let check_time = chrono::Local::now().timestamp_millis();
+    let mut works = vec![];
+    let mut offset = 0;
+    loop {
+        let page = CLIENT
+            .explore_by_author(
+                author.author_path_word.as_str(),
+                Some("-datetime_updated"),
+                offset,
+                PAGE_SIZE,
+            )
+            .await?;
+        let size = page.list.len() as u64;
+        works.extend(page.list);
+        offset += size;
+        if size == 0 || offset >= page.total as u64 {
+            break;
+        }
+    }
+    let known = author_work::known_works().await?;
+    let known_comics: HashSet<&String> = known.iter().map(|(_, comic)| comic).collect();
+    // 第一次检查时记录已有的作品, 不作为新作品
+    let baseline = author.last_check_time == 0;
+    let mut added = HashSet::new();
+    let mut models = vec![];
+    for comic in works {
+        if known.contains(&(author.author_path_word.clone(), comic.path_word.clone()))
+            || !added.insert(comic.path_word.clone())
+        {
+            continue;
+        }
+        // 已经从其他关注的作者发现过的作品不重复提示
+        let is_new = !baseline && !known_comics.contains(&comic.path_word);
+        models.push(work_from_comic(author, comic, check_time, is_new)?);
+    }
+    let found = models.iter().filter(|model| model.is_new).count() as u64;
+    author_work::insert_all(models).await?;
+    followed_author::update_check_time(author.author_path_word.clone(), check_time).await?;
+    Ok(found)
+}
+
+fn work_from_comic(
+    author: &followed_author::Model,
+    comic: ComicInExplore,
+    found_time: i64,
+    is_new: bool,
+) -> anyhow::Result<author_work::Model> {
+    Ok(author_work::Model {
+        author_path_word: author.author_path_word.clone(),
+        comic_path_word: comic.path_word,
+        comic_name: comic.name,
+        comic_authors: serde_json::to_string(&comic.author)?,
+        comic_cover: comic.cover,
+        datetime_updated: comic.datetime_updated.unwrap_or_default(),
+        found_time,
+        is_new,
+        seen: !is_new,
+    })
+}
@@ -0,0 +1,85 @@
+use crate::copy_client::BrowseComic;
+use crate::database::active::{comic_view_log, synced_view_log};
+use crate::database::properties::property;
+use crate::{profile, CLIENT};
+use lazy_static::lazy_static;
+use tokio::sync::Mutex;
+
+const PAGE_SIZE: u64 = 50;
+
+lazy_static! {
+    static ref SYNC_LOCK: Mutex<()> = Mutex::new(());
+}
+
+// 将账号的浏览记录合并到本地的comic_view_log, 返回合并的条数
+pub(crate) async fn sync_browse() -> anyhow::Result<u64> {
+    let _lock = SYNC_LOCK.lock().await;
+    if CLIENT.get_token().await.is_empty() {
+        return Ok(0);
+    }
+    let last_sync_time = property::load_property(profile::key("browse_sync_time").await)
+        .await?
+        .parse::<i64>()
+        .unwrap_or(0);
+    let sync_time = chrono::Local::now().timestamp_millis();
+    // 服务器不返回浏览时间, 第一次同步时视为刚刚浏览,
+    // 之后视为在上次同步时浏览, 上次同步后本地阅读过的漫画以本地为准
+    let base_time = if last_sync_time > 0 {
+        last_sync_time
+    } else {
+        sync_time
+    };
+    let mut models = vec![];
+    let mut offset = 0;
+    loop {
+        let page = CLIENT.browser(offset, PAGE_SIZE).await?;
+        let size = page.list.len() as u64;
+        for browse in page.list {
+            // 服务器按浏览时间倒序, 用序号保持顺序
+            let view_time = base_time - models.len() as i64;
+            models.push(view_log_from_browse(browse, view_time)?);
+        }
+        offset += size;
+        if size == 0 || offset >= page.total as u64 {
+            break;
+        }
+    }
+    let merged = comic_view_log::merge_remote(models).await?;
+    let count = merged.len() as u64;
+    synced_view_log::mark(merged, sync_time).await?;
+    property::save_property(
+        profile::key("browse_sync_time").await,
+        sync_time.to_string(),
+    )
+    .await?;
+    Ok(count)
+}
+
+// 退出登录时调用, 下次登录重新完整同步,
+// wipe_history为true时同时删除从账号同步来的浏览记录, 只在本地阅读的记录保留
+pub(crate) async fn reset(wipe_history: bool) -> anyhow::Result<()> {
+    let _lock = SYNC_LOCK.lock().await;
+    property::delete_property(profile::key("browse_sync_time").await).await?;
+    if wipe_history {
+        comic_view_log::delete_by_comic_path_words(synced_view_log::load_comic_path_words().await?)
+            .await?;
+    }
+    synced_view_log::delete_all().await?;
+    Ok(())
+}
+
+fn view_log_from_browse(
+    browse: BrowseComic,
+    view_time: i64,
+) -> anyhow::Result<comic_view_log::Model> {
+    Ok(comic_view_log::Model {
+        comic_path_word: browse.comic.path_word,
+        comic_name: browse.comic.name,
+        comic_authors: serde_json::to_string(&browse.comic.author)?,
+        comic_cover: browse.comic.cover,
+        chapter_uuid: browse.last_chapter_id,
+        chapter_name: browse.last_chapter_name,
+        view_time,
+        ..Default::default()
+    })
+}
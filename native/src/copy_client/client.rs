@@ -2,23 +2,73 @@ pub use super::types::*;
 use super::{Browse, Comment, Roast};
 use crate::copy_client::{
     BrowseComic, ChapterData, CollectedComic, ComicChapter, ComicData, ComicInExplore,
-    ComicInSearch, ComicQuery, LoginResult, MemberInfo, Page, RankItem, RecommendItem,
-    RegisterResult, Response, Tags,
+    ComicInSearch, ComicQuery, Group, GroupChapters, LoginResult, MemberInfo, Page, RankItem,
+    RecommendItem, RegisterResult, Response, Tags,
 };
 use base64::Engine;
 use chrono::Datelike;
 use rand::prelude::IndexedRandom;
 use rand::Rng;
+use std::future::Future;
 use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+// token失效后自动重新登录的结果
+#[derive(Debug, Clone)]
+pub enum AuthChange {
+    Relogin(Box<LoginResult>),
+    ReloginFailed(String),
+}
+
+pub type AuthCallback = Arc<dyn Fn(AuthChange) + Sync + Send>;
+
+// 自动切换镜像后的回调, 参数为新的api_host
+pub type HostCallback = Arc<dyn Fn(String) + Sync + Send>;
+
+// 读取全部章节时每页的数量
+const ALL_CHAPTERS_PAGE_SIZE: u64 = 100;
+
+// 连续失败多少次后切换镜像
+const FAILOVER_THRESHOLD: u32 = 3;
+const PROBE_PATH: &str = "/api/v3/h5/filter/comic/tags";
+// 探测不使用请求的读取超时, 避免切换镜像时长时间等待
+const PROBE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+#[derive(Debug, Clone)]
+pub struct HostProbe {
+    pub host: String,
+    pub available: bool,
+    pub latency: std::time::Duration,
+    pub message: String,
+}
+
 pub struct Client {
     agent: Mutex<Arc<reqwest::Client>>,
     api_host: Mutex<Arc<String>>,
+    api_hosts: Mutex<Arc<Vec<String>>>,
+    host_failures: AtomicU32,
+    failover_lock: Mutex<()>,
+    host_callback: Mutex<Option<HostCallback>>,
     token: Mutex<Arc<String>>,
     device: Mutex<Arc<String>>,
     device_info: Mutex<Arc<String>>,
+    username: Mutex<Arc<String>>,
+    password: Mutex<Arc<String>>,
+    relogin_lock: Mutex<()>,
+    auth_callback: Mutex<Option<AuthCallback>>,
+    retry_policy: Mutex<Arc<RetryPolicy>>,
+    retry_counters: RetryCounters,
+    offline: AtomicBool,
+}
+
+#[derive(Default)]
+struct RetryCounters {
+    api_retries: AtomicU64,
+    image_retries: AtomicU64,
+    api_exhausted: AtomicU64,
+    image_exhausted: AtomicU64,
 }
 
 impl Client {
@@ -26,9 +76,20 @@ impl Client {
         Self {
             agent: Mutex::new(agent.into()),
             api_host: Mutex::new(Arc::new(api_host.into())),
+            api_hosts: Mutex::new(Arc::new(vec![])),
+            host_failures: AtomicU32::new(0),
+            failover_lock: Mutex::new(()),
+            host_callback: Mutex::new(None),
             token: Mutex::new(Arc::new(String::new())),
             device: Mutex::new(Arc::new("".to_string())),
             device_info: Mutex::new(Arc::new("".to_string())),
+            username: Mutex::new(Arc::new("".to_string())),
+            password: Mutex::new(Arc::new("".to_string())),
+            relogin_lock: Mutex::new(()),
+            auth_callback: Mutex::new(None),
+            retry_policy: Mutex::new(Arc::new(RetryPolicy::default())),
+            retry_counters: RetryCounters::default(),
+            offline: AtomicBool::new(false),
         }
     }
 
@@ -54,6 +115,115 @@ impl Client {
         api_host.clone()
     }
 
+    // 镜像列表, 按优先级排序, 切换时按顺序选择第一个可用的
+    pub async fn set_api_hosts(&self, api_hosts: Vec<String>) {
+        *self.api_hosts.lock().await = Arc::new(api_hosts);
+    }
+
+    pub async fn api_hosts(&self) -> Arc<Vec<String>> {
+        self.api_hosts.lock().await.clone()
+    }
+
+    pub async fn set_host_callback(&self, callback: Option<HostCallback>) {
+        *self.host_callback.lock().await = callback;
+    }
+
+    // 服务器有响应即为可用, 包括未登录等接口错误
+    // 离线模式下不发送请求, 报告为不可用
+    pub async fn probe_host(&self, host: &str) -> HostProbe {
+        if self.is_offline() {
+            return HostProbe {
+                host: host.to_owned(),
+                available: false,
+                latency: std::time::Duration::ZERO,
+                message: ErrorInfo::Offline.to_string(),
+            };
+        }
+        let start = std::time::Instant::now();
+        let result = tokio::time::timeout(
+            PROBE_TIMEOUT,
+            self.send_once::<serde_json::Value>(
+                host,
+                reqwest::Method::GET,
+                PROBE_PATH,
+                serde_json::json!({
+                    "platform": 3,
+                }),
+            ),
+        )
+        .await;
+        let latency = start.elapsed();
+        let (available, message) = match result {
+            Ok(Ok(_)) => (true, "".to_owned()),
+            Ok(Err(err)) => (!is_host_error(&err), err.info.to_string()),
+            Err(_) => (false, "probe timeout".to_owned()),
+        };
+        HostProbe {
+            host: host.to_owned(),
+            available,
+            latency,
+            message,
+        }
+    }
+
+    pub async fn probe_hosts(&self) -> Vec<HostProbe> {
+        let hosts = self.api_hosts().await;
+        futures_util::future::join_all(hosts.iter().map(|host| self.probe_host(host))).await
+    }
+
+    async fn record_host_result<T>(&self, host: &str, result: &Result<T>) {
+        match result {
+            Ok(_) => self.host_failures.store(0, Ordering::Relaxed),
+            Err(err) if is_host_error(err) => {
+                let failures = self.host_failures.fetch_add(1, Ordering::Relaxed) + 1;
+                if failures >= FAILOVER_THRESHOLD {
+                    self.failover(host).await;
+                }
+            }
+            Err(_) => (),
+        }
+    }
+
+    // 当前的镜像连续失败, 同时探测其他镜像并按顺序切换到第一个可用的
+    async fn failover(&self, failed_host: &str) {
+        let _lock = self.failover_lock.lock().await;
+        if self.is_offline() || self.api_host_string().await.as_str() != failed_host {
+            return;
+        }
+        let hosts = self.api_hosts().await;
+        let probes = futures_util::future::join_all(
+            hosts
+                .iter()
+                .filter(|host| host.as_str() != failed_host)
+                .map(|host| self.probe_host(host)),
+        )
+        .await;
+        if let Some(probe) = probes.into_iter().find(|probe| probe.available) {
+            self.set_api_host(probe.host.clone()).await;
+            self.host_failures.store(0, Ordering::Relaxed);
+            let callback = self.host_callback.lock().await.clone();
+            if let Some(callback) = callback {
+                callback(probe.host);
+            }
+        }
+    }
+
+    // 离线模式下所有请求直接返回ErrorInfo::Offline
+    pub fn set_offline(&self, offline: bool) {
+        self.offline.store(offline, Ordering::Relaxed);
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.offline.load(Ordering::Relaxed)
+    }
+
+    fn check_online(&self) -> Result<()> {
+        if self.is_offline() {
+            return Err(Error::from_info(ErrorInfo::Offline));
+        }
+        Ok(())
+    }
+
     pub async fn set_token(&self, token: impl Into<String>) {
         let mut lock = self.token.lock().await;
         *lock = Arc::new(token.into());
@@ -64,12 +234,154 @@ impl Client {
         token.clone()
     }
 
+    // 用于token失效时重新登录, 用户名为空时不会重新登录
+    pub async fn set_credentials(&self, username: impl Into<String>, password: impl Into<String>) {
+        *self.username.lock().await = Arc::new(username.into());
+        *self.password.lock().await = Arc::new(password.into());
+    }
+
+    pub async fn set_auth_callback(&self, callback: Option<AuthCallback>) {
+        *self.auth_callback.lock().await = callback;
+    }
+
+    pub async fn set_retry_policy(&self, policy: RetryPolicy) {
+        *self.retry_policy.lock().await = Arc::new(policy);
+    }
+
+    pub async fn retry_policy(&self) -> Arc<RetryPolicy> {
+        self.retry_policy.lock().await.clone()
+    }
+
+    pub fn retry_stats(&self) -> RetryStats {
+        RetryStats {
+            api_retries: self.retry_counters.api_retries.load(Ordering::Relaxed),
+            image_retries: self.retry_counters.image_retries.load(Ordering::Relaxed),
+            api_exhausted: self.retry_counters.api_exhausted.load(Ordering::Relaxed),
+            image_exhausted: self.retry_counters.image_exhausted.load(Ordering::Relaxed),
+        }
+    }
+
+    // 只重试幂等的请求, 可重试的错误按照retry_policy等待后重试
+    async fn with_retry<T, F, Fut>(
+        &self,
+        idempotent: bool,
+        retries: &AtomicU64,
+        exhausted: &AtomicU64,
+        f: F,
+    ) -> Result<T>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let policy = self.retry_policy().await;
+        let mut attempt = 1;
+        loop {
+            match f().await {
+                Err(err) if idempotent && err.is_retryable() => {
+                    if attempt >= policy.max_attempts {
+                        if attempt > 1 {
+                            exhausted.fetch_add(1, Ordering::Relaxed);
+                        }
+                        return Err(err);
+                    }
+                    retries.fetch_add(1, Ordering::Relaxed);
+                    tokio::time::sleep(policy.delay(attempt)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn notify_auth(&self, change: AuthChange) {
+        let callback = self.auth_callback.lock().await.clone();
+        if let Some(callback) = callback {
+            callback(change);
+        }
+    }
+
+    // token失效时使用保存的用户名密码重新登录, 并重试一次
     pub async fn request<T: for<'de> serde::Deserialize<'de>>(
         &self,
         method: reqwest::Method,
         path: &str,
+        params: serde_json::Value,
+    ) -> Result<T> {
+        let token = self.get_token().await;
+        match self
+            .request_once(method.clone(), path, params.clone())
+            .await
+        {
+            Err(err) if matches!(err.info, ErrorInfo::Unauthorized(_)) && !token.is_empty() => {
+                if self.relogin(token.as_str()).await {
+                    self.request_once(method, path, params).await
+                } else {
+                    Err(err)
+                }
+            }
+            result => result,
+        }
+    }
+
+    // 返回是否已经重新登录, 多个请求同时失效时只登录一次
+    async fn relogin(&self, expired_token: &str) -> bool {
+        let _lock = self.relogin_lock.lock().await;
+        if self.get_token().await.as_str() != expired_token {
+            return true;
+        }
+        let username = self.username.lock().await.clone();
+        let password = self.password.lock().await.clone();
+        if username.is_empty() {
+            return false;
+        }
+        match self.login(username.as_str(), password.as_str()).await {
+            Ok(result) => {
+                self.set_token(result.token.clone()).await;
+                self.notify_auth(AuthChange::Relogin(Box::new(result)))
+                    .await;
+                true
+            }
+            // 账号密码被拒绝时才清除token, 网络等错误保留token, 返回原来的错误
+            Err(err) if matches!(err.info, ErrorInfo::Unauthorized(_) | ErrorInfo::Message(_)) => {
+                self.set_token("").await;
+                self.notify_auth(AuthChange::ReloginFailed(err.info.to_string()))
+                    .await;
+                false
+            }
+            Err(err) => {
+                log::warn!("relogin error: {}", err.info);
+                false
+            }
+        }
+    }
+
+    async fn request_once<T: for<'de> serde::Deserialize<'de>>(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        params: serde_json::Value,
+    ) -> Result<T> {
+        let host = self.api_host_string().await;
+        let result = self
+            .with_retry(
+                method.is_idempotent(),
+                &self.retry_counters.api_retries,
+                &self.retry_counters.api_exhausted,
+                || self.send_once(host.as_str(), method.clone(), path, params.clone()),
+            )
+            .await;
+        self.record_host_result(host.as_str(), &result).await;
+        result
+    }
+
+    async fn send_once<T: for<'de> serde::Deserialize<'de>>(
+        &self,
+        host: &str,
+        method: reqwest::Method,
+        path: &str,
         mut params: serde_json::Value,
     ) -> Result<T> {
+        self.check_online()?;
         let obj = params.as_object_mut().expect("query must be object");
         let device_lock = self.device.lock().await;
         let device = device_lock.deref().deref().clone();
@@ -136,10 +448,7 @@ impl Client {
         let agent_lock = self.agent.lock().await;
         let agent = agent_lock.clone();
         drop(agent_lock);
-        let request = agent.request(
-            method.clone(),
-            format!("{}{}", &self.api_host_string().await.as_str(), path),
-        );
+        let request = agent.request(method.clone(), format!("{}{}", host, path));
         let request = request
             .header(
                 "authorization",
@@ -165,20 +474,39 @@ impl Client {
         let status = response.status();
         let text = response.text().await?;
         if status.as_u16() == 404 {
-            return Err(Error::message("404 Not found"));
+            return Err(Error::classify(404, 0, "404 Not found"));
         }
-        println!("RESPONSE : {} {}", status, text);
-        let value = serde_json::from_str(text.as_str())?;
-        if let serde_json::Value::Object(value) = value {
+        log::debug!("RESPONSE : {} {}", status, text);
+        let value: serde_json::Value = match serde_json::from_str(text.as_str()) {
+            Ok(value) => value,
+            Err(_) if !status.is_success() => {
+                return Err(Error::classify(
+                    status.as_u16(),
+                    0,
+                    status.canonical_reason().unwrap_or_default(),
+                ));
+            }
+            Err(err) => {
+                return Err(Error::from_info(ErrorInfo::InvalidResponse(
+                    err.to_string(),
+                )));
+            }
+        };
+        if let serde_json::Value::Object(value) = &value {
             if value.len() == 1 {
                 if let Some(serde_json::Value::String(detal)) = value.get("detail") {
-                    return Err(Error::message(detal.to_string()));
+                    return Err(Error::classify(status.as_u16(), 0, detal.to_string()));
                 }
             }
         }
-        let response: Response = serde_json::from_str(text.as_str())?;
+        let response: Response = serde_json::from_value(value)
+            .map_err(|err| Error::from_info(ErrorInfo::InvalidResponse(err.to_string())))?;
         if response.code != 200 {
-            return Err(Error::message(response.message));
+            return Err(Error::classify(
+                status.as_u16(),
+                response.code,
+                response.message,
+            ));
         }
         Ok(serde_json::from_value(response.results)?)
     }
@@ -189,7 +517,7 @@ impl Client {
     }
 
     pub async fn register(&self, username: &str, password: &str) -> Result<RegisterResult> {
-        self.request(
+        self.request_once(
             reqwest::Method::POST,
             "/api/v3/register",
             serde_json::json!({
@@ -207,7 +535,7 @@ impl Client {
         let salt = chrono::Local::now().timestamp_millis() % (u16::MAX as i64);
         let password_b64 =
             base64::prelude::BASE64_STANDARD.encode(format!("{}-{}", password, salt).as_bytes());
-        self.request(
+        self.request_once(
             reqwest::Method::POST,
             "/api/v3/login",
             serde_json::json!({
@@ -314,6 +642,49 @@ impl Client {
         .await
     }
 
+    // 分页读取一个分组的全部章节, 按ordered排序
+    pub async fn comic_group_all_chapters(
+        &self,
+        comic_path_word: &str,
+        group_path_word: &str,
+    ) -> Result<Vec<ComicChapter>> {
+        let mut chapters = vec![];
+        loop {
+            let page = self
+                .comic_chapter(
+                    comic_path_word,
+                    group_path_word,
+                    ALL_CHAPTERS_PAGE_SIZE,
+                    chapters.len() as u64,
+                )
+                .await?;
+            let size = page.list.len();
+            chapters.extend(page.list);
+            if size == 0 || chapters.len() as i64 >= page.total {
+                break;
+            }
+        }
+        chapters.sort_by_key(|chapter| chapter.ordered);
+        Ok(chapters)
+    }
+
+    // 同时读取所有分组的章节, 保持groups的顺序
+    pub async fn comic_all_chapters(
+        &self,
+        comic_path_word: &str,
+        groups: Vec<Group>,
+    ) -> Result<Vec<GroupChapters>> {
+        futures_util::future::join_all(groups.into_iter().map(|group| async move {
+            let chapters = self
+                .comic_group_all_chapters(comic_path_word, group.path_word.as_str())
+                .await?;
+            Ok(GroupChapters { group, chapters })
+        }))
+        .await
+        .into_iter()
+        .collect()
+    }
+
     pub async fn comic_query(&self, path_word: &str) -> Result<ComicQuery> {
         self.request(
             reqwest::Method::GET,
@@ -458,20 +829,47 @@ impl Client {
     }
 
     pub async fn download_image(&self, url: &str) -> Result<bytes::Bytes> {
+        self.with_image_retry(|| self.download_image_once(url))
+            .await
+    }
+
+    // 图片下载的重试, f中每次请求前后可以由调用方限速
+    pub async fn with_image_retry<T, F, Fut>(&self, f: F) -> Result<T>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        self.with_retry(
+            true,
+            &self.retry_counters.image_retries,
+            &self.retry_counters.image_exhausted,
+            f,
+        )
+        .await
+    }
+
+    pub async fn download_image_once(&self, url: &str) -> Result<bytes::Bytes> {
+        self.check_online()?;
         let agent_lock = self.agent.lock().await;
         let agent = agent_lock.clone();
         drop(agent_lock);
-        Ok(agent.get(url).send().await?.bytes().await?)
+        Ok(agent
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?)
     }
 
-    pub async fn roasts(&self, chapter_id: &str) -> Result<Page<Roast>> {
+    pub async fn roasts(&self, chapter_id: &str, offset: u64, limit: u64) -> Result<Page<Roast>> {
         self.request(
             reqwest::Method::GET,
             "/api/v3/roasts",
             serde_json::json!({
                 "chapter_id": chapter_id,
-                "limit": 10,
-                "offset": 0,
+                "limit": limit,
+                "offset": offset,
                 "platform": 3,
             }),
         )
@@ -666,3 +1064,12 @@ fn random_android_ua() -> String {
         android_version, device, build_id, firefox_version
     )
 }
+
+// 网络不通, 服务器错误或者返回的不是接口的内容, 可能是镜像失效
+fn is_host_error(err: &Error) -> bool {
+    match &err.info {
+        ErrorInfo::Network(err) => err.status().is_none_or(|status| status.is_server_error()),
+        ErrorInfo::Server(_, _) | ErrorInfo::InvalidResponse(_) => true,
+        _ => false,
+    }
+}
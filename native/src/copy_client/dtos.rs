@@ -173,6 +173,13 @@ pub struct ComicChapter {
     pub uuid: String,
 }
 
+#[napi(object)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupChapters {
+    pub group: Group,
+    pub chapters: Vec<ComicChapter>,
+}
+
 #[napi(object)]
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ComicQuery {
@@ -1,5 +1,6 @@
 use std::backtrace::Backtrace;
 use std::fmt::{Display, Formatter};
+use std::time::Duration;
 
 pub type Result<A> = std::result::Result<A, Error>;
 
@@ -14,45 +15,161 @@ pub enum ErrorInfo {
     Network(reqwest::Error),
     Message(String),
     Convert(serde_json::Error),
+    // 未登录或者token已经失效
+    Unauthorized(String),
+    NotFound(String),
+    RateLimited(String),
+    RegionLocked(String),
+    VipLocked(String),
+    // 服务器错误, http状态码或者接口返回的code
+    Server(u16, String),
+    // 返回的内容不是接口的格式, 例如被防火墙拦截返回的html
+    InvalidResponse(String),
+    // 离线模式, 不请求网络
+    Offline,
     Other(Box<dyn std::error::Error + Sync + Send>),
 }
 
+impl ErrorInfo {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ErrorInfo::Network(_) => "Network",
+            ErrorInfo::Message(_) => "Message",
+            ErrorInfo::Convert(_) => "Convert",
+            ErrorInfo::Unauthorized(_) => "Unauthorized",
+            ErrorInfo::NotFound(_) => "NotFound",
+            ErrorInfo::RateLimited(_) => "RateLimited",
+            ErrorInfo::RegionLocked(_) => "RegionLocked",
+            ErrorInfo::VipLocked(_) => "VipLocked",
+            ErrorInfo::Server(_, _) => "Server",
+            ErrorInfo::InvalidResponse(_) => "InvalidResponse",
+            ErrorInfo::Offline => "Offline",
+            ErrorInfo::Other(_) => "Other",
+        }
+    }
+}
+
+impl Display for ErrorInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErrorInfo::Network(err) => write!(f, "{}", err),
+            ErrorInfo::Convert(err) => write!(f, "{}", err),
+            ErrorInfo::Other(err) => write!(f, "{}", err),
+            ErrorInfo::Server(code, message) => write!(f, "{} {}", code, message),
+            ErrorInfo::Offline => write!(f, "not available offline"),
+            ErrorInfo::Message(message)
+            | ErrorInfo::Unauthorized(message)
+            | ErrorInfo::NotFound(message)
+            | ErrorInfo::RateLimited(message)
+            | ErrorInfo::RegionLocked(message)
+            | ErrorInfo::VipLocked(message)
+            | ErrorInfo::InvalidResponse(message) => write!(f, "{}", message),
+        }
+    }
+}
+
 impl std::error::Error for Error {}
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let mut builder = f.debug_struct("copy_client::Error");
+        builder.field("kind", &self.info.kind());
         match &self.info {
             ErrorInfo::Convert(err) => {
-                builder.field("kind", &"Convert");
                 builder.field("source", err);
             }
             ErrorInfo::Network(err) => {
-                builder.field("kind", &"Network");
-                builder.field("source", err);
-            }
-            ErrorInfo::Message(err) => {
-                builder.field("kind", &"Message");
                 builder.field("source", err);
             }
             ErrorInfo::Other(err) => {
-                builder.field("kind", &"Other");
                 builder.field("source", err);
             }
+            info => {
+                builder.field("source", &info.to_string());
+            }
         }
         builder.finish()
     }
 }
 
 impl Error {
-    pub(crate) fn message(content: impl Into<String>) -> Self {
+    pub(crate) fn from_info(info: ErrorInfo) -> Self {
         Self {
             backtrace: Backtrace::capture(),
-            info: ErrorInfo::Message(content.into()),
+            info,
+        }
+    }
+
+    // 只根据http状态码和接口返回的code判断错误类型, 其他的都是普通的提示信息
+    pub(crate) fn classify(status: u16, code: u16, message: impl Into<String>) -> Self {
+        let message = message.into();
+        let info = match (status, code) {
+            (401, _) | (_, 401) => ErrorInfo::Unauthorized(message),
+            (402, _) | (_, 402) => ErrorInfo::VipLocked(message),
+            (404, _) | (_, 404) => ErrorInfo::NotFound(message),
+            (429, _) | (_, 429) => ErrorInfo::RateLimited(message),
+            (451, _) | (_, 451) => ErrorInfo::RegionLocked(message),
+            _ if status >= 500 => ErrorInfo::Server(status, message),
+            _ if code >= 500 => ErrorInfo::Server(code, message),
+            _ => ErrorInfo::Message(message),
+        };
+        Self::from_info(info)
+    }
+}
+
+impl Error {
+    // 网络超时/连接失败, 限速和服务器错误可以重试
+    pub fn is_retryable(&self) -> bool {
+        match &self.info {
+            ErrorInfo::Network(err) => match err.status() {
+                Some(status) => status.as_u16() == 429 || status.is_server_error(),
+                None => err.is_timeout() || err.is_connect() || err.is_request() || err.is_body(),
+            },
+            ErrorInfo::RateLimited(_) | ErrorInfo::Server(_, _) => true,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    // 包括第一次请求, 1为不重试
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
         }
     }
 }
 
+impl RetryPolicy {
+    // 第attempt次失败后的等待时间, 指数增长, 并随机减少最多一半
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exp = self
+            .base_delay
+            .saturating_mul(1 << attempt.saturating_sub(1).min(16))
+            .min(self.max_delay);
+        exp.mul_f64(rand::random_range(0.5..=1.0))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RetryStats {
+    // 重试的次数
+    pub api_retries: u64,
+    pub image_retries: u64,
+    // 重试后仍然失败的次数
+    pub api_exhausted: u64,
+    pub image_exhausted: u64,
+}
+
 macro_rules! from_error {
     ($error_type:ty, $info_type:path) => {
         impl From<$error_type> for Error {
@@ -68,3 +185,49 @@ macro_rules! from_error {
 
 from_error!(::reqwest::Error, ErrorInfo::Network);
 from_error!(::serde_json::Error, ErrorInfo::Convert);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(status: u16, code: u16, message: &str) -> ErrorInfo {
+        Error::classify(status, code, message).info
+    }
+
+    #[test]
+    fn classify_by_status_and_code() {
+        assert!(matches!(info(401, 0, ""), ErrorInfo::Unauthorized(_)));
+        assert!(matches!(info(200, 401, ""), ErrorInfo::Unauthorized(_)));
+        assert!(matches!(info(402, 0, ""), ErrorInfo::VipLocked(_)));
+        assert!(matches!(info(404, 0, ""), ErrorInfo::NotFound(_)));
+        assert!(matches!(info(200, 429, ""), ErrorInfo::RateLimited(_)));
+        assert!(matches!(info(451, 0, ""), ErrorInfo::RegionLocked(_)));
+        assert!(matches!(info(502, 0, ""), ErrorInfo::Server(502, _)));
+        assert!(matches!(info(200, 500, ""), ErrorInfo::Server(500, _)));
+    }
+
+    #[test]
+    fn classify_ignores_message() {
+        for message in ["请先登录", "vip", "付费", "region", "请求超速"] {
+            assert!(matches!(info(400, 210, message), ErrorInfo::Message(_)));
+        }
+    }
+
+    #[test]
+    fn retry_delay_grows_with_jitter_and_cap() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(1),
+        };
+        for _ in 0..100 {
+            let first = policy.delay(1);
+            assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100));
+            let third = policy.delay(3);
+            assert!(third >= Duration::from_millis(200) && third <= Duration::from_millis(400));
+            let capped = policy.delay(10);
+            assert!(capped >= Duration::from_millis(500) && capped <= Duration::from_secs(1));
+            assert!(policy.delay(u32::MAX) <= Duration::from_secs(1));
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use crate::database::active::ACTIVE_DATABASE;
+use crate::database::{create_index, create_table_if_not_exists, index_exists};
+use sea_orm::entity::prelude::*;
+use sea_orm::{IntoActiveModel, Order, QueryOrder, QuerySelect, Select};
+use std::collections::HashSet;
+use std::ops::Deref;
+
+// 关注的作者已经见过的作品, is_new 的记录就是新作品动态
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "author_work")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub author_path_word: String,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub comic_path_word: String,
+    pub comic_name: String,
+    pub comic_authors: String,
+    pub comic_cover: String,
+    pub datetime_updated: String,
+    pub found_time: i64,
+    pub is_new: bool,
+    pub seen: bool,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+pub(crate) async fn init() {
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    create_table_if_not_exists(db.deref(), Entity).await;
+    if !index_exists(db.deref(), "author_work", "author_work_idx_found_time").await {
+        create_index(
+            db.deref(),
+            "author_work",
+            vec!["found_time"],
+            "author_work_idx_found_time",
+        )
+        .await;
+    }
+}
+
+// (author_path_word, comic_path_word)
+pub(crate) async fn known_works() -> anyhow::Result<HashSet<(String, String)>> {
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    Ok(Entity::find()
+        .select_only()
+        .column(Column::AuthorPathWord)
+        .column(Column::ComicPathWord)
+        .into_tuple::<(String, String)>()
+        .all(db.deref())
+        .await?
+        .into_iter()
+        .collect())
+}
+
+pub(crate) async fn insert_all(models: Vec<Model>) -> anyhow::Result<()> {
+    if models.is_empty() {
+        return Ok(());
+    }
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    Entity::insert_many(models.into_iter().map(|model| model.into_active_model()))
+        .exec(db.deref())
+        .await?;
+    Ok(())
+}
+
+pub(crate) async fn delete_by_author_path_word(author_path_word: String) -> anyhow::Result<()> {
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    Entity::delete_many()
+        .filter(Column::AuthorPathWord.eq(author_path_word))
+        .exec(db.deref())
+        .await?;
+    Ok(())
+}
+
+fn feed_select(only_unseen: bool) -> Select<Entity> {
+    let select = Entity::find().filter(Column::IsNew.eq(true));
+    if only_unseen {
+        select.filter(Column::Seen.eq(false))
+    } else {
+        select
+    }
+}
+
+pub(crate) async fn load_feed(
+    only_unseen: bool,
+    offset: u64,
+    limit: u64,
+) -> anyhow::Result<Vec<Model>> {
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    Ok(feed_select(only_unseen)
+        .order_by(Column::FoundTime, Order::Desc)
+        .offset(offset)
+        .limit(limit)
+        .all(db.deref())
+        .await?)
+}
+
+pub(crate) async fn feed_count(only_unseen: bool) -> anyhow::Result<u64> {
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    Ok(feed_select(only_unseen).count(db.deref()).await?)
+}
+
+// comic_path_words为空时全部标记为已读
+pub(crate) async fn mark_seen(comic_path_words: Vec<String>) -> anyhow::Result<()> {
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    let update = Entity::update_many().col_expr(Column::Seen, Expr::value(true));
+    if comic_path_words.is_empty() {
+        update.exec(db.deref()).await?;
+    } else {
+        update
+            .filter(Column::ComicPathWord.is_in(comic_path_words))
+            .exec(db.deref())
+            .await?;
+    }
+    Ok(())
+}
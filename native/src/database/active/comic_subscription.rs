@@ -0,0 +1,159 @@
+use crate::database::active::ACTIVE_DATABASE;
+use crate::database::{create_index, create_table_if_not_exists, index_exists};
+use sea_orm::entity::prelude::*;
+use sea_orm::{IntoActiveModel, Order, QueryOrder, QuerySelect};
+use std::collections::HashSet;
+use std::ops::Deref;
+
+const DELETE_CHUNK_SIZE: usize = 500;
+
+// 收藏(账号收藏和本地收藏)的漫画最后一次看到的最新章节, 每个账号单独记录
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "comic_subscription")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub profile: String,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub comic_path_word: String,
+    pub comic_name: String,
+    pub comic_cover: String,
+    pub last_chapter_uuid: String,
+    pub last_chapter_name: String,
+    pub datetime_updated: String,
+    pub check_time: i64,
+    // 有未读的新章节
+    pub has_update: bool,
+    pub update_time: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+pub(crate) async fn init() {
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    create_table_if_not_exists(db.deref(), Entity).await;
+    if !index_exists(
+        db.deref(),
+        "comic_subscription",
+        "comic_subscription_idx_profile_update_time",
+    )
+    .await
+    {
+        create_index(
+            db.deref(),
+            "comic_subscription",
+            vec!["profile", "update_time"],
+            "comic_subscription_idx_profile_update_time",
+        )
+        .await;
+    }
+}
+
+pub(crate) async fn load_all(profile: &str) -> anyhow::Result<Vec<Model>> {
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    Ok(Entity::find()
+        .filter(Column::Profile.eq(profile))
+        .all(db.deref())
+        .await?)
+}
+
+pub(crate) async fn save(model: Model) -> anyhow::Result<()> {
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    if Entity::find_by_id((model.profile.clone(), model.comic_path_word.clone()))
+        .one(db.deref())
+        .await?
+        .is_some()
+    {
+        model
+            .into_active_model()
+            .reset_all()
+            .update(db.deref())
+            .await?;
+    } else {
+        model.into_active_model().insert(db.deref()).await?;
+    }
+    Ok(())
+}
+
+// 取消收藏的漫画不再跟踪, 收藏很多时分批删除, 避免超过SQLite的参数数量限制
+pub(crate) async fn delete_not_in(
+    profile: &str,
+    comic_path_words: Vec<String>,
+) -> anyhow::Result<()> {
+    let keep = comic_path_words.into_iter().collect::<HashSet<_>>();
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    let removed = Entity::find()
+        .select_only()
+        .column(Column::ComicPathWord)
+        .filter(Column::Profile.eq(profile))
+        .into_tuple::<String>()
+        .all(db.deref())
+        .await?
+        .into_iter()
+        .filter(|comic_path_word| !keep.contains(comic_path_word))
+        .collect::<Vec<_>>();
+    for chunk in removed.chunks(DELETE_CHUNK_SIZE) {
+        Entity::delete_many()
+            .filter(Column::Profile.eq(profile))
+            .filter(Column::ComicPathWord.is_in(chunk.to_vec()))
+            .exec(db.deref())
+            .await?;
+    }
+    Ok(())
+}
+
+pub(crate) async fn delete_by_profile(profile: &str) -> anyhow::Result<()> {
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    Entity::delete_many()
+        .filter(Column::Profile.eq(profile))
+        .exec(db.deref())
+        .await?;
+    Ok(())
+}
+
+pub(crate) async fn load_updates(
+    profile: &str,
+    offset: u64,
+    limit: u64,
+) -> anyhow::Result<Vec<Model>> {
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    Ok(Entity::find()
+        .filter(Column::Profile.eq(profile))
+        .filter(Column::HasUpdate.eq(true))
+        .order_by(Column::UpdateTime, Order::Desc)
+        .offset(offset)
+        .limit(limit)
+        .all(db.deref())
+        .await?)
+}
+
+pub(crate) async fn updates_count(profile: &str) -> anyhow::Result<u64> {
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    Ok(Entity::find()
+        .filter(Column::Profile.eq(profile))
+        .filter(Column::HasUpdate.eq(true))
+        .count(db.deref())
+        .await?)
+}
+
+// comic_path_words为空时清除全部
+pub(crate) async fn clear_updates(
+    profile: &str,
+    comic_path_words: Vec<String>,
+) -> anyhow::Result<()> {
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    let update = Entity::update_many()
+        .col_expr(Column::HasUpdate, Expr::value(false))
+        .filter(Column::Profile.eq(profile));
+    if comic_path_words.is_empty() {
+        update.exec(db.deref()).await?;
+    } else {
+        update
+            .filter(Column::ComicPathWord.is_in(comic_path_words))
+            .exec(db.deref())
+            .await?;
+    }
+    Ok(())
+}
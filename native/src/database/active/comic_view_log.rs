@@ -108,3 +108,82 @@ pub(crate) async fn view_log_by_comic_path_word(
     let db = ACTIVE_DATABASE.get().unwrap().lock().await;
     Ok(Entity::find_by_id(path_word).one(db.deref()).await?)
 }
+
+pub(crate) async fn delete_by_comic_path_words(
+    comic_path_words: Vec<String>,
+) -> anyhow::Result<()> {
+    if comic_path_words.is_empty() {
+        return Ok(());
+    }
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    Entity::delete_many()
+        .filter(Column::ComicPathWord.is_in(comic_path_words))
+        .exec(db.deref())
+        .await?;
+    Ok(())
+}
+
+// 合并服务器的浏览记录, 每部漫画view_time较新的一方为准, 返回合并的漫画
+pub(crate) async fn merge_remote(models: Vec<Model>) -> anyhow::Result<Vec<String>> {
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    let mut merged = vec![];
+    for model in models {
+        let comic_path_word = model.comic_path_word.clone();
+        if let Some(in_db) = Entity::find_by_id(comic_path_word.clone())
+            .one(db.deref())
+            .await?
+        {
+            if !remote_wins(&in_db, &model) {
+                continue;
+            }
+            // 章节变了, 本地的章节序号和页码不再适用, 使用服务器的记录(页码从头开始)
+            let mut in_db = in_db.into_active_model();
+            in_db.chapter_uuid = Set(model.chapter_uuid);
+            in_db.chapter_name = Set(model.chapter_name);
+            in_db.chapter_ordered = Set(model.chapter_ordered);
+            in_db.chapter_size = Set(model.chapter_size);
+            in_db.chapter_count = Set(model.chapter_count);
+            in_db.page_rank = Set(model.page_rank);
+            in_db.view_time = Set(model.view_time);
+            in_db.update(db.deref()).await?;
+        } else {
+            model.into_active_model().insert(db.deref()).await?;
+        }
+        merged.push(comic_path_word);
+    }
+    Ok(merged)
+}
+
+// 同一章节不需要合并, 否则服务器的记录更新时才覆盖本地
+fn remote_wins(local: &Model, remote: &Model) -> bool {
+    local.chapter_uuid != remote.chapter_uuid && remote.view_time > local.view_time
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(chapter_uuid: &str, view_time: i64) -> Model {
+        Model {
+            chapter_uuid: chapter_uuid.to_owned(),
+            view_time,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn newer_remote_chapter_wins() {
+        assert!(remote_wins(&log("a", 1), &log("b", 2)));
+    }
+
+    #[test]
+    fn older_or_same_time_remote_is_ignored() {
+        assert!(!remote_wins(&log("a", 2), &log("b", 1)));
+        assert!(!remote_wins(&log("a", 2), &log("b", 2)));
+    }
+
+    #[test]
+    fn same_chapter_is_ignored() {
+        assert!(!remote_wins(&log("a", 1), &log("a", 2)));
+    }
+}
@@ -0,0 +1,116 @@
+use crate::database::active::ACTIVE_DATABASE;
+use crate::database::{create_index, create_table_if_not_exists, index_exists};
+use sea_orm::entity::prelude::*;
+use sea_orm::{IntoActiveModel, Order, QueryOrder, QuerySelect, Set};
+use std::ops::Deref;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "followed_author")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub author_path_word: String,
+    pub author_name: String,
+    pub follow_time: i64,
+    // 0 为还没有检查过, 第一次检查的作品不算作新作品
+    pub last_check_time: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+pub(crate) async fn init() {
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    create_table_if_not_exists(db.deref(), Entity).await;
+    if !index_exists(
+        db.deref(),
+        "followed_author",
+        "followed_author_idx_follow_time",
+    )
+    .await
+    {
+        create_index(
+            db.deref(),
+            "followed_author",
+            vec!["follow_time"],
+            "followed_author_idx_follow_time",
+        )
+        .await;
+    }
+}
+
+// 重复关注只更新作者名称
+pub(crate) async fn follow(author_path_word: String, author_name: String) -> anyhow::Result<()> {
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    if let Some(in_db) = Entity::find_by_id(author_path_word.clone())
+        .one(db.deref())
+        .await?
+    {
+        let mut in_db = in_db.into_active_model();
+        in_db.author_name = Set(author_name);
+        in_db.update(db.deref()).await?;
+    } else {
+        Model {
+            author_path_word,
+            author_name,
+            follow_time: chrono::Local::now().timestamp_millis(),
+            last_check_time: 0,
+        }
+        .into_active_model()
+        .insert(db.deref())
+        .await?;
+    }
+    Ok(())
+}
+
+pub(crate) async fn delete_by_author_path_word(author_path_word: String) -> anyhow::Result<()> {
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    Entity::delete_by_id(author_path_word)
+        .exec(db.deref())
+        .await?;
+    Ok(())
+}
+
+pub(crate) async fn find_by_author_path_word(
+    author_path_word: String,
+) -> anyhow::Result<Option<Model>> {
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    Ok(Entity::find_by_id(author_path_word).one(db.deref()).await?)
+}
+
+pub(crate) async fn load_followed_authors(offset: u64, limit: u64) -> anyhow::Result<Vec<Model>> {
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    Ok(Entity::find()
+        .order_by(Column::FollowTime, Order::Desc)
+        .offset(offset)
+        .limit(limit)
+        .all(db.deref())
+        .await?)
+}
+
+pub(crate) async fn load_all() -> anyhow::Result<Vec<Model>> {
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    Ok(Entity::find()
+        .order_by(Column::FollowTime, Order::Asc)
+        .all(db.deref())
+        .await?)
+}
+
+pub(crate) async fn count() -> anyhow::Result<u64> {
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    Ok(Entity::find().count(db.deref()).await?)
+}
+
+pub(crate) async fn update_check_time(
+    author_path_word: String,
+    last_check_time: i64,
+) -> anyhow::Result<()> {
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    Entity::update_many()
+        .filter(Column::AuthorPathWord.eq(author_path_word))
+        .col_expr(Column::LastCheckTime, Expr::value(last_check_time))
+        .exec(db.deref())
+        .await?;
+    Ok(())
+}
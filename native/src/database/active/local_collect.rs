@@ -1,6 +1,8 @@
 use crate::database::active::ACTIVE_DATABASE;
-use crate::database::create_table_if_not_exists;
+use crate::database::{create_index, create_table_if_not_exists, escape_like, index_exists};
 use sea_orm::entity::prelude::*;
+use sea_orm::sea_query::{Condition, LikeExpr};
+use sea_orm::{IntoActiveModel, Order, QueryOrder, QuerySelect, Select};
 use serde_derive::{Deserialize, Serialize};
 use std::convert::TryInto;
 use std::ops::Deref;
@@ -45,4 +47,102 @@ impl ActiveModelBehavior for ActiveModel {}
 pub(crate) async fn init() {
     let db = ACTIVE_DATABASE.get().unwrap().lock().await;
     create_table_if_not_exists(db.deref(), Entity).await;
+    if !index_exists(db.deref(), "local_collect", "local_collect_idx_append_time").await {
+        create_index(
+            db.deref(),
+            "local_collect",
+            vec!["append_time"],
+            "local_collect_idx_append_time",
+        )
+        .await;
+    }
+}
+
+pub(crate) async fn save(mut model: Model) -> anyhow::Result<()> {
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    if let Some(in_db) = Entity::find_by_id(model.path_word.clone())
+        .one(db.deref())
+        .await?
+    {
+        // 重复收藏只更新漫画信息, 保留收藏时间
+        model.append_time = in_db.append_time;
+        model
+            .into_active_model()
+            .reset_all()
+            .update(db.deref())
+            .await?;
+    } else {
+        model.append_time = chrono::Local::now().timestamp_millis();
+        model.into_active_model().insert(db.deref()).await?;
+    }
+    Ok(())
+}
+
+pub(crate) async fn delete_by_path_word(path_word: String) -> anyhow::Result<()> {
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    Entity::delete_by_id(path_word).exec(db.deref()).await?;
+    Ok(())
+}
+
+pub(crate) async fn find_by_path_word(path_word: String) -> anyhow::Result<Option<Model>> {
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    Ok(Entity::find_by_id(path_word).one(db.deref()).await?)
+}
+
+// ordering : append_time / name / datetime_updated, 以"-"开头为倒序
+fn order(select: Select<Entity>, ordering: &str) -> anyhow::Result<Select<Entity>> {
+    let (column, order) = match ordering.strip_prefix('-') {
+        Some(column) => (column, Order::Desc),
+        None => (ordering, Order::Asc),
+    };
+    let column = match column {
+        "append_time" => Column::AppendTime,
+        "name" => Column::Name,
+        "datetime_updated" => Column::DatetimeUpdated,
+        _ => return Err(anyhow::anyhow!("unknown ordering : {}", ordering)),
+    };
+    Ok(select.order_by(column, order))
+}
+
+pub(crate) async fn load_local_collects(
+    ordering: &str,
+    offset: u64,
+    limit: u64,
+) -> anyhow::Result<Vec<Model>> {
+    let select = order(Entity::find(), ordering)?;
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    Ok(select.offset(offset).limit(limit).all(db.deref()).await?)
+}
+
+pub(crate) async fn count() -> anyhow::Result<u64> {
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    Ok(Entity::find().count(db.deref()).await?)
+}
+
+// 关键字中的%和_不作为通配符
+fn search_condition(keyword: &str) -> Condition {
+    let like = format!("%{}%", escape_like(keyword));
+    Condition::any()
+        .add(Column::Name.like(LikeExpr::new(like.as_str()).escape('\\')))
+        .add(Column::Alias.like(LikeExpr::new(like.as_str()).escape('\\')))
+        .add(Column::Author.like(LikeExpr::new(like.as_str()).escape('\\')))
+}
+
+pub(crate) async fn search(
+    keyword: &str,
+    ordering: &str,
+    offset: u64,
+    limit: u64,
+) -> anyhow::Result<Vec<Model>> {
+    let select = order(Entity::find().filter(search_condition(keyword)), ordering)?;
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    Ok(select.offset(offset).limit(limit).all(db.deref()).await?)
+}
+
+pub(crate) async fn search_count(keyword: &str) -> anyhow::Result<u64> {
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    Ok(Entity::find()
+        .filter(search_condition(keyword))
+        .count(db.deref())
+        .await?)
 }
@@ -2,8 +2,12 @@ use crate::database::connect_db;
 use once_cell::sync::OnceCell;
 use sea_orm::DatabaseConnection;
 use tokio::sync::Mutex;
+pub(crate) mod author_work;
+pub(crate) mod comic_subscription;
 pub(crate) mod comic_view_log;
+pub(crate) mod followed_author;
 pub(crate) mod local_collect;
+pub(crate) mod synced_view_log;
 
 pub(crate) static ACTIVE_DATABASE: OnceCell<Mutex<DatabaseConnection>> = OnceCell::new();
 pub(crate) async fn init() {
@@ -12,4 +16,8 @@ pub(crate) async fn init() {
     // init tables
     comic_view_log::init().await;
     local_collect::init().await;
+    followed_author::init().await;
+    author_work::init().await;
+    comic_subscription::init().await;
+    synced_view_log::init().await;
 }
@@ -0,0 +1,63 @@
+use crate::database::active::ACTIVE_DATABASE;
+use crate::database::create_table_if_not_exists;
+use sea_orm::entity::prelude::*;
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{IntoActiveModel, QuerySelect};
+use std::ops::Deref;
+
+// 从账号浏览记录同步到comic_view_log的漫画, 退出登录时用于只删除同步来的记录
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "synced_view_log")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub comic_path_word: String,
+    pub sync_time: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+pub(crate) async fn init() {
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    create_table_if_not_exists(db.deref(), Entity).await;
+}
+
+pub(crate) async fn mark(comic_path_words: Vec<String>, sync_time: i64) -> anyhow::Result<()> {
+    if comic_path_words.is_empty() {
+        return Ok(());
+    }
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    Entity::insert_many(comic_path_words.into_iter().map(|comic_path_word| {
+        Model {
+            comic_path_word,
+            sync_time,
+        }
+        .into_active_model()
+    }))
+    .on_conflict(
+        OnConflict::column(Column::ComicPathWord)
+            .update_column(Column::SyncTime)
+            .to_owned(),
+    )
+    .exec(db.deref())
+    .await?;
+    Ok(())
+}
+
+pub(crate) async fn load_comic_path_words() -> anyhow::Result<Vec<String>> {
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    Ok(Entity::find()
+        .select_only()
+        .column(Column::ComicPathWord)
+        .into_tuple::<String>()
+        .all(db.deref())
+        .await?)
+}
+
+pub(crate) async fn delete_all() -> anyhow::Result<()> {
+    let db = ACTIVE_DATABASE.get().unwrap().lock().await;
+    Entity::delete_many().exec(db.deref()).await?;
+    Ok(())
+}
@@ -1,13 +1,18 @@
 use crate::copy_client;
 use crate::database::cache::CACHE_DATABASE;
-use crate::database::{create_index, create_table_if_not_exists, index_exists};
+use crate::database::{create_index, create_table_if_not_exists, escape_like, index_exists};
+use crate::CLIENT;
+use lazy_static::lazy_static;
 use sea_orm::entity::prelude::*;
+use sea_orm::sea_query::{LikeExpr, OnConflict};
 use sea_orm::IntoActiveModel;
+use std::collections::HashSet;
 use std::convert::TryInto;
 use std::future::Future;
 use std::ops::Deref;
 use std::pin::Pin;
 use std::time::Duration;
+use tokio::sync::Mutex;
 
 #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
 #[sea_orm(table_name = "web_cache")]
@@ -38,6 +43,50 @@ pub(crate) async fn init() {
     }
 }
 
+// 缓存过期后的处理方式
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum CachePolicy {
+    // 过期后必须从网络获取
+    FreshOnly,
+    // 过期后先返回旧的内容, 同时在后台更新
+    StaleWhileRevalidate,
+    // 过期后从网络获取, 失败时返回旧的内容
+    StaleIfError,
+}
+
+impl CachePolicy {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            CachePolicy::FreshOnly => "fresh_only",
+            CachePolicy::StaleWhileRevalidate => "stale_while_revalidate",
+            CachePolicy::StaleIfError => "stale_if_error",
+        }
+    }
+
+    pub(crate) fn parse(policy: &str) -> Option<CachePolicy> {
+        match policy {
+            "fresh_only" => Some(CachePolicy::FreshOnly),
+            "stale_while_revalidate" => Some(CachePolicy::StaleWhileRevalidate),
+            "stale_if_error" => Some(CachePolicy::StaleIfError),
+            _ => None,
+        }
+    }
+}
+
+lazy_static! {
+    // 默认先返回过期的缓存并在后台更新, 使用cache_with_policy的接口才会受设置影响
+    pub(crate) static ref CACHE_POLICY: Mutex<CachePolicy> =
+        Mutex::new(CachePolicy::StaleWhileRevalidate);
+    // 正在后台更新的key, 同一个key只更新一次
+    static ref REVALIDATING: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
+}
+
+// stale为true时value是已经过期的缓存
+pub(crate) struct Cached<T> {
+    pub(crate) value: T,
+    pub(crate) stale: bool,
+}
+
 pub(crate) async fn cache_first<T: for<'de> serde::Deserialize<'de> + serde::Serialize>(
     key: String,
     expire: Duration,
@@ -47,7 +96,8 @@ pub(crate) async fn cache_first<T: for<'de> serde::Deserialize<'de> + serde::Ser
     let db = CACHE_DATABASE.get().unwrap().lock().await;
     let in_db = Entity::find_by_id(key.clone()).one(db.deref()).await?;
     if let Some(ref model) = in_db {
-        if time < (model.cache_time + expire.as_millis() as i64) {
+        // 离线模式下过期的缓存也可以使用
+        if CLIENT.is_offline() || time < (model.cache_time + expire.as_millis() as i64) {
             return Ok(serde_json::from_str(&model.cache_content)?);
         }
     };
@@ -73,6 +123,25 @@ pub(crate) async fn cache_first<T: for<'de> serde::Deserialize<'de> + serde::Ser
     Ok(t)
 }
 
+// 没有过期的缓存, 离线模式下过期的也可以使用, 不请求网络
+pub(crate) async fn load_fresh<T: for<'de> serde::Deserialize<'de>>(
+    key: &str,
+    expire: Duration,
+) -> anyhow::Result<Option<T>> {
+    let time = chrono::Local::now().timestamp_millis();
+    let in_db = Entity::find_by_id(key)
+        .one(CACHE_DATABASE.get().unwrap().lock().await.deref())
+        .await?;
+    match in_db {
+        Some(model)
+            if CLIENT.is_offline() || time < (model.cache_time + expire.as_millis() as i64) =>
+        {
+            Ok(Some(serde_json::from_str(&model.cache_content)?))
+        }
+        _ => Ok(None),
+    }
+}
+
 pub(crate) async fn cache_first_map<
     T: for<'de> serde::Deserialize<'de> + serde::Serialize,
     R: From<T>,
@@ -84,6 +153,107 @@ pub(crate) async fn cache_first_map<
     Ok(R::from(cache_first(key, expire, pin).await?))
 }
 
+// 与cache_first相同, 但是按照CACHE_POLICY处理过期的缓存, 请求网络时不锁定数据库
+pub(crate) async fn cache_with_policy<
+    T: for<'de> serde::Deserialize<'de> + serde::Serialize + Send + 'static,
+>(
+    key: String,
+    expire: Duration,
+    pin: Pin<Box<dyn Future<Output = copy_client::Result<T>> + Sync + Send>>,
+) -> anyhow::Result<Cached<T>> {
+    let policy = *CACHE_POLICY.lock().await;
+    let time = chrono::Local::now().timestamp_millis();
+    let in_db = Entity::find_by_id(key.clone())
+        .one(CACHE_DATABASE.get().unwrap().lock().await.deref())
+        .await?;
+    let stale = match in_db {
+        Some(model) => {
+            if time < (model.cache_time + expire.as_millis() as i64) {
+                return Ok(Cached {
+                    value: serde_json::from_str(&model.cache_content)?,
+                    stale: false,
+                });
+            }
+            serde_json::from_str::<T>(&model.cache_content).ok()
+        }
+        None => None,
+    };
+    let stale = match (policy, stale) {
+        (_, Some(value)) if CLIENT.is_offline() => {
+            return Ok(Cached { value, stale: true });
+        }
+        (CachePolicy::StaleWhileRevalidate, Some(value)) => {
+            if !REVALIDATING.lock().await.insert(key.clone()) {
+                return Ok(Cached { value, stale: true });
+            }
+            tokio::spawn(async move {
+                let result = match pin.await {
+                    Ok(t) => match serde_json::to_string(&t) {
+                        Ok(content) => save(key.clone(), content).await,
+                        Err(e) => Err(e.into()),
+                    },
+                    Err(e) => Err(e.into()),
+                };
+                if let Err(e) = result {
+                    log::warn!("revalidate cache {} error: {:?}", key, e);
+                }
+                REVALIDATING.lock().await.remove(&key);
+            });
+            return Ok(Cached { value, stale: true });
+        }
+        (CachePolicy::StaleIfError, stale) => stale,
+        _ => None,
+    };
+    match pin.await {
+        Ok(t) => {
+            save(key, serde_json::to_string(&t)?).await?;
+            Ok(Cached {
+                value: t,
+                stale: false,
+            })
+        }
+        Err(e) => match stale {
+            Some(value) => {
+                log::warn!("use stale cache {} : {:?}", key, e);
+                Ok(Cached { value, stale: true })
+            }
+            None => Err(e.into()),
+        },
+    }
+}
+
+pub(crate) async fn cache_map_with_policy<
+    T: for<'de> serde::Deserialize<'de> + serde::Serialize + Send + 'static,
+    R: From<T>,
+>(
+    key: String,
+    expire: Duration,
+    pin: Pin<Box<dyn Future<Output = copy_client::Result<T>> + Sync + Send>>,
+) -> anyhow::Result<Cached<R>> {
+    let cached = cache_with_policy(key, expire, pin).await?;
+    Ok(Cached {
+        value: R::from(cached.value),
+        stale: cached.stale,
+    })
+}
+
+async fn save(key: String, content: String) -> anyhow::Result<()> {
+    let model = Model {
+        cache_key: key,
+        cache_content: content,
+        cache_time: chrono::Local::now().timestamp_millis(),
+    };
+    Entity::insert(model.into_active_model())
+        .on_conflict(
+            OnConflict::column(Column::CacheKey)
+                .update_columns([Column::CacheContent, Column::CacheTime])
+                .to_owned(),
+        )
+        .exec(CACHE_DATABASE.get().unwrap().lock().await.deref())
+        .await?;
+    Ok(())
+}
+
 pub(crate) async fn clean_web_cache_by_time(time: i64) -> anyhow::Result<()> {
     Entity::delete_many()
         .filter(Column::CacheTime.lt(time))
@@ -92,10 +262,37 @@ pub(crate) async fn clean_web_cache_by_time(time: i64) -> anyhow::Result<()> {
     Ok(())
 }
 
-pub(crate) async fn clean_web_cache_by_like(like: &str) -> anyhow::Result<()> {
+pub(crate) async fn clean_web_cache_by_key(key: &str) -> anyhow::Result<()> {
     Entity::delete_many()
-        .filter(Column::CacheKey.like(like))
+        .filter(Column::CacheKey.eq(key))
         .exec(CACHE_DATABASE.get().unwrap().lock().await.deref())
         .await?;
     Ok(())
 }
+
+// 删除以prefix开头的缓存, prefix中的_和%不作为通配符
+pub(crate) async fn clean_web_cache_by_prefix(prefix: &str) -> anyhow::Result<()> {
+    let pattern = format!("{}%", escape_like(prefix));
+    Entity::delete_many()
+        .filter(Column::CacheKey.like(LikeExpr::new(pattern).escape('\\')))
+        .exec(CACHE_DATABASE.get().unwrap().lock().await.deref())
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_policy_round_trip() {
+        for policy in [
+            CachePolicy::FreshOnly,
+            CachePolicy::StaleWhileRevalidate,
+            CachePolicy::StaleIfError,
+        ] {
+            assert_eq!(CachePolicy::parse(policy.as_str()), Some(policy));
+        }
+        assert_eq!(CachePolicy::parse(""), None);
+    }
+}
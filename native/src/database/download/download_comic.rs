@@ -250,3 +250,9 @@ pub(crate) async fn find_by_uuid_list(uuid_list: &[String]) -> anyhow::Result<Ve
         .await?;
     Ok(models)
 }
+
+pub(crate) async fn find_by_path_word(path_word: &str) -> anyhow::Result<Option<Model>> {
+    Ok(Entity::find_by_id(path_word)
+        .one(DOWNLOAD_DATABASE.get().unwrap().lock().await.deref())
+        .await?)
+}
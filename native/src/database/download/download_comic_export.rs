@@ -0,0 +1,89 @@
+use crate::database::create_table_if_not_exists;
+use crate::database::download::DOWNLOAD_DATABASE;
+use sea_orm::entity::prelude::*;
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{DeleteResult, IntoActiveModel};
+use serde_derive::{Deserialize, Serialize};
+use std::ops::Deref;
+
+pub(crate) const STATUS_EXPORT_SUCCESS: i64 = 1;
+pub(crate) const STATUS_EXPORT_FAILED: i64 = 2;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "download_comic_export")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub comic_path_word: String,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub export_to: String,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub exports_type: String,
+    pub export_status: i64,
+    pub message: String,
+    pub export_time: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+pub(crate) async fn init() {
+    let db = DOWNLOAD_DATABASE.get().unwrap().lock().await;
+    create_table_if_not_exists(db.deref(), Entity).await;
+}
+
+pub(crate) async fn is_exported(
+    comic_path_word: &str,
+    export_to: &str,
+    exports_type: &str,
+) -> anyhow::Result<bool> {
+    let db = DOWNLOAD_DATABASE.get().unwrap().lock().await;
+    let count = Entity::find()
+        .filter(Column::ComicPathWord.eq(comic_path_word))
+        .filter(Column::ExportTo.eq(export_to))
+        .filter(Column::ExportsType.eq(exports_type))
+        .filter(Column::ExportStatus.eq(STATUS_EXPORT_SUCCESS))
+        .count(db.deref())
+        .await?;
+    Ok(count > 0)
+}
+
+pub(crate) async fn save(model: Model) -> anyhow::Result<()> {
+    let db = DOWNLOAD_DATABASE.get().unwrap().lock().await;
+    Entity::insert(model.into_active_model())
+        .on_conflict(
+            OnConflict::columns(vec![
+                Column::ComicPathWord,
+                Column::ExportTo,
+                Column::ExportsType,
+            ])
+            .update_columns(vec![
+                Column::ExportStatus,
+                Column::Message,
+                Column::ExportTime,
+            ])
+            .to_owned(),
+        )
+        .exec(db.deref())
+        .await?;
+    Ok(())
+}
+
+pub(crate) async fn find_by_comic_path_word(comic_path_word: &str) -> anyhow::Result<Vec<Model>> {
+    let db = DOWNLOAD_DATABASE.get().unwrap().lock().await;
+    Ok(Entity::find()
+        .filter(Column::ComicPathWord.eq(comic_path_word))
+        .all(db.deref())
+        .await?)
+}
+
+pub(crate) async fn delete_by_comic_path_word(
+    db: &impl ConnectionTrait,
+    comic_path_word: &str,
+) -> Result<DeleteResult, DbErr> {
+    Entity::delete_many()
+        .filter(Column::ComicPathWord.eq(comic_path_word))
+        .exec(db)
+        .await
+}
@@ -7,6 +7,7 @@ use tokio::sync::Mutex;
 
 pub(crate) mod download_comic;
 pub(crate) mod download_comic_chapter;
+pub(crate) mod download_comic_export;
 pub(crate) mod download_comic_group;
 pub(crate) mod download_comic_page;
 
@@ -20,6 +21,7 @@ pub(crate) async fn init() {
     download_comic_group::init().await;
     download_comic_chapter::init().await;
     download_comic_page::init().await;
+    download_comic_export::init().await;
 }
 
 pub(crate) async fn save_chapter_images(
@@ -111,6 +113,40 @@ pub(crate) async fn remove_all(comic_path_word: String) -> anyhow::Result<()> {
             download_comic_group::delete_by_comic_path_word(db, comic_path_word.as_str()).await?;
             download_comic_chapter::delete_by_comic_path_word(db, comic_path_word.as_str()).await?;
             download_comic_page::delete_by_comic_path_word(db, comic_path_word.as_str()).await?;
+            download_comic_export::delete_by_comic_path_word(db, comic_path_word.as_str()).await?;
+            Ok::<(), DbErr>(())
+        })
+    })
+    .await?;
+    Ok(())
+}
+
+// 导入的漫画直接为下载成功的状态, 已存在时先删除旧的记录
+pub(crate) async fn import_comic(
+    comic: download_comic::Model,
+    groups: Vec<download_comic_group::Model>,
+    chapters: Vec<download_comic_chapter::Model>,
+    pages: Vec<download_comic_page::Model>,
+) -> anyhow::Result<()> {
+    let db = DOWNLOAD_DATABASE.get().unwrap().lock().await;
+    db.transaction(|db| {
+        Box::pin(async move {
+            let comic_path_word = comic.path_word.clone();
+            download_comic::delete_by_comic_path_word(db, comic_path_word.as_str()).await?;
+            download_comic_group::delete_by_comic_path_word(db, comic_path_word.as_str()).await?;
+            download_comic_chapter::delete_by_comic_path_word(db, comic_path_word.as_str()).await?;
+            download_comic_page::delete_by_comic_path_word(db, comic_path_word.as_str()).await?;
+            download_comic_export::delete_by_comic_path_word(db, comic_path_word.as_str()).await?;
+            download_comic::insert_or_update_info(db, comic).await?;
+            for group in groups {
+                download_comic_group::insert_or_update_info(db, group).await?;
+            }
+            for chapter in chapters {
+                download_comic_chapter::insert_or_update_info(db, chapter).await?;
+            }
+            for page in pages {
+                download_comic_page::save(db, page).await?;
+            }
             Ok::<(), DbErr>(())
         })
     })
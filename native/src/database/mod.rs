@@ -16,16 +16,17 @@ pub(crate) async fn init_database() {
 }
 
 pub(crate) async fn connect_db(path: &str) -> DatabaseConnection {
-    println!("CONNECT TO DB : {}", path);
+    log::info!("CONNECT TO DB : {}", path);
     let path = join_paths(vec![get_database_dir().as_str(), path]);
-    println!("DB PATH : {}", path);
+    log::debug!("DB PATH : {}", path);
     let url = format!("sqlite:{}?mode=rwc", path);
     let mut opt = sea_orm::ConnectOptions::new(url);
     opt.max_connections(20)
         .min_connections(5)
         .connect_timeout(Duration::from_secs(8))
         .idle_timeout(Duration::from_secs(8))
-        .sqlx_logging(true);
+        .sqlx_logging(true)
+        .sqlx_logging_level(log::LevelFilter::Debug);
     sea_orm::Database::connect(opt).await.unwrap()
 }
 
@@ -112,3 +113,11 @@ pub(crate) async fn create_index(
 ) {
     create_index_a(db, table_name, columns, index_name, false).await
 }
+
+// 转义LIKE中的\, %和_, 配合 LikeExpr::escape('\\') 按原样匹配
+pub(crate) fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
@@ -4,6 +4,7 @@ use sea_orm::DatabaseConnection;
 use tokio::sync::Mutex;
 
 pub(crate) mod property;
+pub(crate) mod secret;
 
 pub(crate) static PROPERTIES_DATABASE: OnceCell<Mutex<DatabaseConnection>> = OnceCell::new();
 
@@ -12,4 +13,5 @@ pub(crate) async fn init() {
     PROPERTIES_DATABASE.set(Mutex::new(db)).unwrap();
     // init tables
     property::init().await;
+    secret::init().await;
 }
@@ -4,6 +4,10 @@ use sea_orm::entity::prelude::*;
 use sea_orm::IntoActiveModel;
 use sea_orm::Set;
 use std::ops::Deref;
+
+// 这些值只能通过secret模块读写, 加密保存
+pub(crate) const SECRET_KEYS: [&str; 2] = ["password", "token"];
+
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
 #[sea_orm(table_name = "property")]
 pub struct Model {
@@ -26,6 +30,7 @@ pub(crate) async fn init() {
 }
 
 pub async fn save_property(k: String, v: String) -> anyhow::Result<()> {
+    check_not_secret(k.as_str())?;
     let db = PROPERTIES_DATABASE.get().unwrap().lock().await;
     if let Some(in_db) = Entity::find_by_id(k.clone()).one(db.deref()).await? {
         let mut in_db = in_db.into_active_model();
@@ -41,6 +46,7 @@ pub async fn save_property(k: String, v: String) -> anyhow::Result<()> {
 }
 
 pub async fn load_property(k: String) -> anyhow::Result<String> {
+    check_not_secret(k.as_str())?;
     let in_db = Entity::find_by_id(k)
         .one(PROPERTIES_DATABASE.get().unwrap().lock().await.deref())
         .await?;
@@ -50,3 +56,23 @@ pub async fn load_property(k: String) -> anyhow::Result<String> {
         "".to_owned()
     })
 }
+
+fn check_not_secret(k: &str) -> anyhow::Result<()> {
+    if SECRET_KEYS.contains(&base_key(k)) {
+        return Err(anyhow::anyhow!("property {} is a secret", k));
+    }
+    Ok(())
+}
+
+// 去掉账号后缀, 例如 token@work 返回 token
+pub(crate) fn base_key(k: &str) -> &str {
+    k.split('@').next().unwrap_or(k)
+}
+
+pub async fn delete_property(k: String) -> anyhow::Result<()> {
+    check_not_secret(k.as_str())?;
+    Entity::delete_by_id(k)
+        .exec(PROPERTIES_DATABASE.get().unwrap().lock().await.deref())
+        .await?;
+    Ok(())
+}
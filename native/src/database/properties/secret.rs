@@ -0,0 +1,158 @@
+use crate::database::properties::property::SECRET_KEYS;
+use crate::database::properties::{property, PROPERTIES_DATABASE};
+use crate::database::{create_index, create_table_if_not_exists, index_exists};
+use crate::{get_root, join_paths};
+use aes_gcm::aead::Aead;
+use aes_gcm::{Aes256Gcm, Key, KeyInit, Nonce};
+use base64::Engine;
+use once_cell::sync::OnceCell;
+use sea_orm::entity::prelude::*;
+use sea_orm::Set;
+use sea_orm::{IntoActiveModel, TransactionTrait};
+use std::ops::Deref;
+
+// 密钥文件不放在database目录中, 每次安装随机生成
+const KEY_FILE: &str = "secret.key";
+const NONCE_LEN: usize = 12;
+
+static CIPHER: OnceCell<Aes256Gcm> = OnceCell::new();
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "secret")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub k: String,
+    // base64(nonce + 密文)
+    pub v: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+pub(crate) async fn init() {
+    CIPHER.set(load_cipher()).ok();
+    {
+        let db = PROPERTIES_DATABASE.get().unwrap().lock().await;
+        create_table_if_not_exists(db.deref(), Entity).await;
+        if !index_exists(db.deref(), "secret", "secret_idx_k").await {
+            create_index(db.deref(), "secret", vec!["k"], "secret_idx_k").await;
+        }
+    }
+    // 迁移失败时明文保留在property表中, 下次启动再迁移
+    if let Err(e) = migrate().await {
+        log::warn!("migrate secret error: {:?}", e);
+    }
+}
+
+fn load_cipher() -> Aes256Gcm {
+    let path = join_paths(vec![get_root().as_str(), KEY_FILE]);
+    let key = match std::fs::read(path.as_str()) {
+        Ok(key) if key.len() == 32 => key,
+        _ => {
+            let key = rand::random::<[u8; 32]>().to_vec();
+            std::fs::write(path.as_str(), &key).unwrap();
+            key
+        }
+    };
+    Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key.as_slice()))
+}
+
+// 旧版本明文保存在property表中, 加密写入secret表后在同一个事务中删除明文
+async fn migrate() -> anyhow::Result<()> {
+    let db = PROPERTIES_DATABASE.get().unwrap().lock().await;
+    for k in SECRET_KEYS {
+        let Some(plain) = property::Entity::find_by_id(k.to_owned())
+            .one(db.deref())
+            .await?
+        else {
+            continue;
+        };
+        let v = if plain.v.is_empty() {
+            None
+        } else {
+            Some(encrypt(plain.v.as_str())?)
+        };
+        let k = k.to_owned();
+        db.transaction(|db| {
+            Box::pin(async move {
+                if let Some(v) = v {
+                    save(db, k.clone(), v).await?;
+                }
+                property::Entity::delete_by_id(k).exec(db).await?;
+                Ok::<(), DbErr>(())
+            })
+        })
+        .await?;
+    }
+    Ok(())
+}
+
+fn encrypt(v: &str) -> anyhow::Result<String> {
+    let nonce = rand::random::<[u8; NONCE_LEN]>();
+    let mut data = nonce.to_vec();
+    data.extend(
+        CIPHER
+            .get()
+            .unwrap()
+            .encrypt(Nonce::from_slice(&nonce), v.as_bytes())
+            .map_err(|_| anyhow::anyhow!("encrypt secret failed"))?,
+    );
+    Ok(base64::prelude::BASE64_STANDARD.encode(data))
+}
+
+fn decrypt(v: &str) -> anyhow::Result<String> {
+    let data = base64::prelude::BASE64_STANDARD.decode(v)?;
+    if data.len() < NONCE_LEN {
+        return Err(anyhow::anyhow!("invalid secret"));
+    }
+    let (nonce, data) = data.split_at(NONCE_LEN);
+    let plain = CIPHER
+        .get()
+        .unwrap()
+        .decrypt(Nonce::from_slice(nonce), data)
+        .map_err(|_| anyhow::anyhow!("decrypt secret failed"))?;
+    Ok(String::from_utf8(plain)?)
+}
+
+pub(crate) async fn save_secret(k: String, v: String) -> anyhow::Result<()> {
+    let v = encrypt(v.as_str())?;
+    let db = PROPERTIES_DATABASE.get().unwrap().lock().await;
+    save(db.deref(), k, v).await?;
+    Ok(())
+}
+
+// v为已经加密的值
+async fn save(db: &impl ConnectionTrait, k: String, v: String) -> Result<(), DbErr> {
+    if let Some(in_db) = Entity::find_by_id(k.clone()).one(db).await? {
+        let mut in_db = in_db.into_active_model();
+        in_db.v = Set(v);
+        in_db.update(db).await?;
+    } else {
+        Model { k, v }.into_active_model().insert(db).await?;
+    }
+    Ok(())
+}
+
+// 密钥丢失或数据损坏时当作未保存, 需要重新登录
+pub(crate) async fn load_secret(k: String) -> anyhow::Result<String> {
+    let in_db = Entity::find_by_id(k)
+        .one(PROPERTIES_DATABASE.get().unwrap().lock().await.deref())
+        .await?;
+    Ok(if let Some(in_db) = in_db {
+        decrypt(in_db.v.as_str()).unwrap_or_else(|e| {
+            log::warn!("load secret error: {:?}", e);
+            "".to_owned()
+        })
+    } else {
+        "".to_owned()
+    })
+}
+
+pub(crate) async fn delete_secret(k: String) -> anyhow::Result<()> {
+    Entity::delete_by_id(k)
+        .exec(PROPERTIES_DATABASE.get().unwrap().lock().await.deref())
+        .await?;
+    Ok(())
+}
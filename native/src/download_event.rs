@@ -0,0 +1,123 @@
+use crate::udto::UiDownloadEvent;
+use lazy_static::lazy_static;
+use napi_ohos::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi_ohos::Status;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+pub(crate) const COMIC_STARTED: &str = "COMIC_STARTED";
+pub(crate) const CHAPTER_FETCHED: &str = "CHAPTER_FETCHED";
+pub(crate) const CHAPTER_FAILED: &str = "CHAPTER_FAILED";
+pub(crate) const PAGE_DOWNLOADED: &str = "PAGE_DOWNLOADED";
+pub(crate) const PAGE_FAILED: &str = "PAGE_FAILED";
+pub(crate) const COMIC_COMPLETED: &str = "COMIC_COMPLETED";
+pub(crate) const COMIC_FAILED: &str = "COMIC_FAILED";
+pub(crate) const PAUSED: &str = "PAUSED";
+pub(crate) const RESUMED: &str = "RESUMED";
+
+pub(crate) type DownloadListener =
+    ThreadsafeFunction<UiDownloadEvent, (), UiDownloadEvent, Status, false>;
+
+// 正在下载的漫画的图片计数, 与download_comic.image_count/image_count_success保持一致
+#[derive(Default)]
+struct Progress {
+    comic_path_word: String,
+    image_count: i64,
+    image_count_success: i64,
+}
+
+lazy_static! {
+    static ref LISTENER: std::sync::Mutex<Option<Arc<DownloadListener>>> =
+        std::sync::Mutex::new(None);
+    static ref PROGRESS: Mutex<Progress> = Mutex::new(Progress::default());
+}
+
+pub(crate) fn set_listener(listener: Option<DownloadListener>) {
+    *LISTENER.lock().unwrap() = listener.map(Arc::new);
+}
+
+fn emit(event: UiDownloadEvent) {
+    let listener = LISTENER.lock().unwrap().clone();
+    if let Some(listener) = listener {
+        listener.call(event, ThreadsafeFunctionCallMode::NonBlocking);
+    }
+}
+
+async fn emit_progress(
+    event_type: &str,
+    chapter_uuid: Option<String>,
+    image_index: Option<i32>,
+    bytes: i64,
+) {
+    let progress = PROGRESS.lock().await;
+    emit(UiDownloadEvent {
+        event_type: event_type.to_owned(),
+        comic_path_word: progress.comic_path_word.clone(),
+        chapter_uuid,
+        image_index,
+        bytes,
+        image_count: progress.image_count,
+        image_count_success: progress.image_count_success,
+    });
+}
+
+pub(crate) async fn comic_started(
+    comic_path_word: &str,
+    image_count: i64,
+    image_count_success: i64,
+) {
+    *PROGRESS.lock().await = Progress {
+        comic_path_word: comic_path_word.to_owned(),
+        image_count,
+        image_count_success,
+    };
+    emit_progress(COMIC_STARTED, None, None, 0).await;
+}
+
+pub(crate) async fn chapter_fetched(chapter_uuid: &str, page_count: i64) {
+    PROGRESS.lock().await.image_count += page_count;
+    emit_progress(CHAPTER_FETCHED, Some(chapter_uuid.to_owned()), None, 0).await;
+}
+
+pub(crate) async fn chapter_failed(chapter_uuid: &str) {
+    emit_progress(CHAPTER_FAILED, Some(chapter_uuid.to_owned()), None, 0).await;
+}
+
+pub(crate) async fn page_downloaded(chapter_uuid: &str, image_index: i32, bytes: i64) {
+    PROGRESS.lock().await.image_count_success += 1;
+    emit_progress(
+        PAGE_DOWNLOADED,
+        Some(chapter_uuid.to_owned()),
+        Some(image_index),
+        bytes,
+    )
+    .await;
+}
+
+pub(crate) async fn page_failed(chapter_uuid: &str, image_index: i32) {
+    emit_progress(
+        PAGE_FAILED,
+        Some(chapter_uuid.to_owned()),
+        Some(image_index),
+        0,
+    )
+    .await;
+}
+
+pub(crate) async fn comic_finished(success: bool) {
+    emit_progress(
+        if success {
+            COMIC_COMPLETED
+        } else {
+            COMIC_FAILED
+        },
+        None,
+        None,
+        0,
+    )
+    .await;
+}
+
+pub(crate) async fn pause_changed(pause: bool) {
+    emit_progress(if pause { PAUSED } else { RESUMED }, None, None, 0).await;
+}
@@ -1,5 +1,11 @@
+use crate::copy_client::ErrorInfo;
 use crate::database::download;
-use crate::database::download::{download_comic, download_comic_chapter, download_comic_page};
+use crate::database::download::{
+    download_comic, download_comic_chapter, download_comic_export, download_comic_page,
+};
+use crate::database::properties::property;
+use crate::download_event;
+use crate::rate_limit::{is_throttled_status, HostRateLimiter};
 use crate::udto::UiQueryDownloadComic;
 use crate::utils::{create_dir_if_not_exists, join_paths};
 use crate::{get_download_dir, CLIENT};
@@ -31,8 +37,75 @@ pub(crate) fn get_cover_path(model: &download_comic::Model) -> String {
 lazy_static! {
     pub(crate) static ref RESTART_FLAG: Mutex<bool> = Mutex::new(false);
     pub(crate) static ref DOWNLOAD_AND_EXPORT_TO: Mutex<String> = Mutex::new("".to_owned());
+    pub(crate) static ref DOWNLOAD_AND_EXPORT_TYPE: Mutex<String> = Mutex::new("Zip".to_owned());
     pub(crate) static ref DOWNLOAD_THREAD: Mutex<i32> = Mutex::new(3);
     pub(crate) static ref PAUSE_FLAG: Mutex<bool> = Mutex::new(false);
+    pub(crate) static ref DOWNLOAD_LIMITER: HostRateLimiter = HostRateLimiter::new(0.0);
+}
+
+pub(crate) async fn load_download_settings() -> anyhow::Result<()> {
+    if let Ok(thread) = property::load_property("download_thread".to_owned())
+        .await?
+        .parse::<i32>()
+    {
+        *DOWNLOAD_THREAD.lock().await = thread;
+    }
+    if let Ok(rate) = property::load_property("download_rate_limit".to_owned())
+        .await?
+        .parse::<f64>()
+    {
+        DOWNLOAD_LIMITER.set_rate(rate).await;
+    }
+    Ok(())
+}
+
+pub(crate) async fn download_thread() -> i32 {
+    *DOWNLOAD_THREAD.lock().await
+}
+
+pub(crate) async fn set_download_thread(thread: i32) -> anyhow::Result<()> {
+    if !(1..=16).contains(&thread) {
+        return Err(anyhow::anyhow!("download thread must be in 1..=16"));
+    }
+    property::save_property("download_thread".to_owned(), thread.to_string()).await?;
+    *DOWNLOAD_THREAD.lock().await = thread;
+    Ok(())
+}
+
+pub(crate) async fn download_rate_limit() -> f64 {
+    DOWNLOAD_LIMITER.rate().await
+}
+
+pub(crate) async fn set_download_rate_limit(rate: f64) -> anyhow::Result<()> {
+    if !rate.is_finite() || rate < 0.0 {
+        return Err(anyhow::anyhow!("download rate limit must be >= 0"));
+    }
+    property::save_property("download_rate_limit".to_owned(), rate.to_string()).await?;
+    DOWNLOAD_LIMITER.set_rate(rate).await;
+    Ok(())
+}
+
+// 限速下载, 每次重试都重新取令牌, 遇到429/5xx时对该域名退避
+async fn download_with_limit(url: &str) -> crate::copy_client::Result<bytes::Bytes> {
+    CLIENT
+        .with_image_retry(|| async {
+            DOWNLOAD_LIMITER.acquire(url).await;
+            let result = CLIENT.download_image_once(url).await;
+            match &result {
+                Ok(_) => DOWNLOAD_LIMITER.on_success(url).await,
+                Err(err) => {
+                    if let ErrorInfo::Network(e) = &err.info {
+                        if let Some(status) = e.status() {
+                            if is_throttled_status(status.as_u16()) {
+                                DOWNLOAD_LIMITER.on_throttled(url).await;
+                            }
+                        }
+                    }
+                }
+            }
+            result
+        })
+        .await
 }
 
 async fn need_restart() -> bool {
@@ -49,7 +122,8 @@ async fn set_restart() {
 
 async fn download_pause() -> bool {
     let pause_flag = PAUSE_FLAG.lock().await;
-    let pausing = *pause_flag.deref();
+    // 离线模式时同样暂停
+    let pausing = *pause_flag.deref() || CLIENT.is_offline();
     drop(pause_flag);
     if pausing {
         tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
@@ -67,12 +141,10 @@ pub(crate) async fn download_set_pause(pause: bool) {
     *pause_flag = pause;
     drop(pause_flag);
     set_restart().await;
-    crate::database::properties::property::save_property(
-        "download_pause".to_owned(),
-        pause.to_string(),
-    )
-    .await
-    .expect("save download_pause");
+    property::save_property("download_pause".to_owned(), pause.to_string())
+        .await
+        .expect("save download_pause");
+    download_event::pause_changed(pause).await;
 }
 
 pub async fn start_download() {
@@ -116,6 +188,12 @@ async fn down_next_comic() -> anyhow::Result<()> {
     {
         let comic_dir = join_paths(vec![get_download_dir().as_str(), comic.path_word.as_str()]);
         create_dir_if_not_exists(comic_dir.as_str());
+        download_event::comic_started(
+            comic.path_word.as_str(),
+            comic.image_count,
+            comic.image_count_success,
+        )
+        .await;
         if comic.cover_download_status == download_comic::STATUS_INIT {
             down_cover(&comic).await;
         }
@@ -145,7 +223,7 @@ async fn down_next_comic() -> anyhow::Result<()> {
 }
 
 async fn down_cover(comic: &download_comic::Model) {
-    if let Ok(data) = CLIENT.download_image(comic.cover.as_str()).await {
+    if let Ok(data) = download_with_limit(comic.cover.as_str()).await {
         if let Ok(format) = image::guess_format(&data) {
             let format = if let Some(format) = format.extensions_str().first() {
                 format.to_string()
@@ -207,17 +285,22 @@ async fn fetch_chapter(chapter: &download_comic_chapter::Model) -> anyhow::Resul
                     ..Default::default()
                 });
             }
+            let page_count = images.len() as i64;
             download::save_chapter_images(
                 chapter.comic_path_word.clone(),
                 chapter.uuid.clone(),
                 images,
             )
             .await
-            .expect("save_chapter_images")
+            .expect("save_chapter_images");
+            download_event::chapter_fetched(chapter.uuid.as_str(), page_count).await;
+        }
+        Err(_) => {
+            download::chapter_fetch_error(chapter.uuid.clone())
+                .await
+                .expect("chapter_fetch_error");
+            download_event::chapter_failed(chapter.uuid.as_str()).await;
         }
-        Err(_) => download::chapter_fetch_error(chapter.uuid.clone())
-            .await
-            .expect("chapter_fetch_error"),
     };
     Ok(())
 }
@@ -265,7 +348,7 @@ async fn download_images(comic_path_word: String) {
         //
         for x in results {
             if let Err(e) = x {
-                println!("download_line error: {:?}", e);
+                log::error!("download_line error: {:?}", e);
             }
         }
     }
@@ -291,7 +374,7 @@ async fn download_line(
 }
 
 async fn download_image(image: download_comic_page::Model) {
-    if let Ok(data) = CLIENT.download_image(image.url.as_str()).await {
+    if let Ok(data) = download_with_limit(image.url.as_str()).await {
         if let Ok(format) = image::guess_format(&data) {
             let format = if let Some(format) = format.extensions_str().first() {
                 format.to_string()
@@ -302,12 +385,13 @@ async fn download_image(image: download_comic_page::Model) {
                 let width = image_.width();
                 let height = image_.height();
                 let path = get_image_path(&image);
+                let bytes = data.len() as i64;
                 tokio::fs::write(path.as_str(), data)
                     .await
                     .expect("write image");
                 download::download_page_success(
                     image.comic_path_word,
-                    image.chapter_uuid,
+                    image.chapter_uuid.clone(),
                     image.image_index,
                     width,
                     height,
@@ -315,6 +399,12 @@ async fn download_image(image: download_comic_page::Model) {
                 )
                 .await
                 .expect("download_page_success");
+                download_event::page_downloaded(
+                    image.chapter_uuid.as_str(),
+                    image.image_index,
+                    bytes,
+                )
+                .await;
                 return;
             }
         }
@@ -322,6 +412,7 @@ async fn download_image(image: download_comic_page::Model) {
     download::download_page_failed(image.chapter_uuid.clone(), image.image_index)
         .await
         .expect("download_page_failed");
+    download_event::page_failed(image.chapter_uuid.as_str(), image.image_index).await;
 }
 
 async fn setup_download_status(comic_path_word: String) {
@@ -342,6 +433,68 @@ async fn setup_download_status(comic_path_word: String) {
     download_comic::update_status(comic_path_word.as_str(), comic_status)
         .await
         .expect("update_status");
+    download_event::comic_finished(comic_status == download_comic::STATUS_DOWNLOAD_SUCCESS).await;
+    if comic_status == download_comic::STATUS_DOWNLOAD_SUCCESS {
+        if let Err(e) = export_after_download(comic_path_word.as_str()).await {
+            log::error!("export_after_download error: {:?}", e);
+        }
+    }
+}
+
+pub(crate) async fn download_and_export_to() -> String {
+    DOWNLOAD_AND_EXPORT_TO.lock().await.clone()
+}
+
+pub(crate) async fn set_download_and_export_to(path: String) -> anyhow::Result<()> {
+    property::save_property("download_and_export_to".to_owned(), path.clone()).await?;
+    *DOWNLOAD_AND_EXPORT_TO.lock().await = path;
+    Ok(())
+}
+
+pub(crate) async fn download_and_export_type() -> String {
+    DOWNLOAD_AND_EXPORT_TYPE.lock().await.clone()
+}
+
+pub(crate) async fn set_download_and_export_type(exports_type: String) -> anyhow::Result<()> {
+    if !crate::exports::is_exports_type(exports_type.as_str()) {
+        return Err(anyhow::anyhow!("unknown exports type"));
+    }
+    property::save_property("download_and_export_type".to_owned(), exports_type.clone()).await?;
+    *DOWNLOAD_AND_EXPORT_TYPE.lock().await = exports_type;
+    Ok(())
+}
+
+// 下载完成后自动导出到DOWNLOAD_AND_EXPORT_TO, 每个漫画每个位置和类型只导出一次
+async fn export_after_download(comic_path_word: &str) -> anyhow::Result<()> {
+    let export_to = download_and_export_to().await;
+    if export_to.is_empty() {
+        return Ok(());
+    }
+    let exports_type = download_and_export_type().await;
+    if download_comic_export::is_exported(comic_path_word, &export_to, &exports_type).await? {
+        return Ok(());
+    }
+    let comic = download_comic::find_by_path_word(comic_path_word)
+        .await?
+        .with_context(|| "download comic not found")?;
+    let result = crate::exports::export_comic(&comic, &export_to, &exports_type).await;
+    download_comic_export::save(download_comic_export::Model {
+        comic_path_word: comic_path_word.to_owned(),
+        export_to,
+        exports_type,
+        export_status: if result.is_ok() {
+            download_comic_export::STATUS_EXPORT_SUCCESS
+        } else {
+            download_comic_export::STATUS_EXPORT_FAILED
+        },
+        message: match &result {
+            Ok(_) => "".to_owned(),
+            Err(e) => e.to_string(),
+        },
+        export_time: chrono::Local::now().timestamp_millis(),
+    })
+    .await?;
+    result
 }
 
 pub(crate) fn url_to_cache_key(url_str: &str) -> String {
@@ -1,13 +1,16 @@
 use crate::{
     database::download::{download_comic, download_comic_chapter, download_comic_page},
     downloading::{get_cover_path, get_image_path},
+    pdf_writer::PdfWriter,
     utils::{allowed_file_name, create_dir_if_not_exists, join_paths},
 };
 use anyhow::{Context, Ok, Result};
 use async_trait::async_trait;
 use async_zip::{tokio::write::ZipFileWriter, ZipEntryBuilder};
-use futures_util::{lock::Mutex, AsyncWriteExt};
-use tokio::io::AsyncReadExt;
+use futures_util::lock::Mutex;
+use image::ImageDecoder;
+use itertools::Itertools;
+use serde_derive::Deserialize;
 
 pub(crate) async fn exports(
     uuid_list: Vec<String>,
@@ -20,48 +23,68 @@ pub(crate) async fn exports(
             return Err(anyhow::anyhow!("comic not downloaded"));
         }
     }
-    let exports_type = exports_type.clone();
     for download_comic in &download_comics {
-        let name = download_comic.name.as_str();
-        let mut exporter = match exports_type.as_str() {
-            "Folder" => FolderExporter::on_start(&export_to_folder, &name).await?,
-            "Zip" => ZipExporter::on_start(&export_to_folder, &name).await?,
-            _ => return Err(anyhow::anyhow!("unknown exports type")),
-        };
-        let chapters =
-            download_comic_chapter::find_by_comic_path_word(download_comic.path_word.as_str())
-                .await?;
-        let download_cover_path = get_cover_path(download_comic);
-        exporter
-            .on_cover(
-                download_cover_path.as_str(),
-                download_comic.cover_format.as_str(),
-            )
-            .await?;
-        for chapter in &chapters {
-            exporter.on_chapter(&chapter.name).await?;
-            let pages = download_comic_page::find_by_comic_path_word_and_chapter_uuid(
-                download_comic.path_word.as_str(),
-                chapter.uuid.as_str(),
-            )
-            .await?;
-            for page in &pages {
-                let download_comic_path = get_image_path(&page);
-                exporter
-                    .on_page(&download_comic_path, &page.format, page.image_index)
-                    .await?;
-            }
+        export_comic(download_comic, &export_to_folder, &exports_type).await?;
+    }
+    Ok(())
+}
+
+pub(crate) fn is_exports_type(exports_type: &str) -> bool {
+    matches!(
+        exports_type,
+        "Folder" | "Zip" | "Cbz" | "CbzComic" | "Epub" | "Pdf"
+    )
+}
+
+pub(crate) async fn export_comic(
+    download_comic: &download_comic::Model,
+    export_to_folder: &str,
+    exports_type: &str,
+) -> Result<()> {
+    let name = download_comic.name.as_str();
+    let mut exporter = match exports_type {
+        "Folder" => FolderExporter::on_start(export_to_folder, name).await?,
+        "Zip" => ZipExporter::on_start(export_to_folder, name).await?,
+        "Cbz" => CbzExporter::on_start(export_to_folder, download_comic, false).await?,
+        "CbzComic" => CbzExporter::on_start(export_to_folder, download_comic, true).await?,
+        "Epub" => EpubExporter::on_start(export_to_folder, download_comic).await?,
+        "Pdf" => PdfExporter::on_start(export_to_folder, download_comic).await?,
+        _ => return Err(anyhow::anyhow!("unknown exports type")),
+    };
+    let chapters =
+        download_comic_chapter::find_by_comic_path_word(download_comic.path_word.as_str()).await?;
+    let download_cover_path = get_cover_path(download_comic);
+    exporter
+        .on_cover(
+            download_cover_path.as_str(),
+            download_comic.cover_format.as_str(),
+        )
+        .await?;
+    for chapter in &chapters {
+        let pages = download_comic_page::find_by_comic_path_word_and_chapter_uuid(
+            download_comic.path_word.as_str(),
+            chapter.uuid.as_str(),
+        )
+        .await?;
+        exporter.on_chapter(chapter, pages.len()).await?;
+        for page in &pages {
+            let download_comic_path = get_image_path(page);
+            exporter.on_page(&download_comic_path, page).await?;
         }
-        exporter.finish().await?;
     }
+    exporter.finish().await?;
     Ok(())
 }
 
 #[async_trait]
 trait Exporter {
     async fn on_cover(&mut self, source: &str, format: &str) -> Result<()>;
-    async fn on_chapter(&mut self, name: &str) -> Result<()>;
-    async fn on_page(&mut self, source: &str, format: &str, index: i32) -> Result<()>;
+    async fn on_chapter(
+        &mut self,
+        chapter: &download_comic_chapter::Model,
+        page_count: usize,
+    ) -> Result<()>;
+    async fn on_page(&mut self, source: &str, page: &download_comic_page::Model) -> Result<()>;
     async fn finish(mut self: Box<Self>) -> Result<()>;
 }
 
@@ -92,10 +115,14 @@ impl Exporter for FolderExporter {
         Ok(())
     }
 
-    async fn on_chapter(&mut self, name: &str) -> Result<()> {
+    async fn on_chapter(
+        &mut self,
+        chapter: &download_comic_chapter::Model,
+        _page_count: usize,
+    ) -> Result<()> {
         let path = join_paths(vec![
             self.comic_folder.as_str(),
-            allowed_file_name(name).as_str(),
+            allowed_file_name(&chapter.name).as_str(),
         ]);
         create_dir_if_not_exists(path.as_str());
         let mut lock = self.chaper_folder.lock().await;
@@ -103,14 +130,14 @@ impl Exporter for FolderExporter {
         Ok(())
     }
 
-    async fn on_page(&mut self, source: &str, format: &str, index: i32) -> Result<()> {
+    async fn on_page(&mut self, source: &str, page: &download_comic_page::Model) -> Result<()> {
         let chapter_folder = self.chaper_folder.lock().await;
         let cf = chapter_folder
             .as_ref()
             .with_context(|| "chapter folder not found")?;
         let page_path = join_paths(vec![
             cf.as_str(),
-            format!("{:04}.{}", index, format).as_str(),
+            format!("{:04}.{}", page.image_index, page.format).as_str(),
         ]);
         tokio::fs::copy(source, page_path.as_str()).await?;
         Ok(())
@@ -140,23 +167,30 @@ impl ZipExporter {
     }
 
     async fn push_file(&mut self, source: &str, target: &str) -> Result<()> {
-        let mut file = tokio::fs::File::open(source).await?;
-        let builder = ZipEntryBuilder::new(target.into(), async_zip::Compression::Deflate);
-        let mut entry_writer = self.writer.write_entry_stream(builder).await?;
-        let mut buf = vec![0; 1 << 8];
-        let mut a;
-        while {
-            a = file.read(buf.as_mut_slice()).await?;
-            a > 0
-        } {
-            entry_writer.write_all(&buf[0..a]).await?;
-        }
-        // tokio::io::copy(&mut file, &mut entry_writer).await?;
-        entry_writer.close().await?;
-        Ok(())
+        push_file(&mut self.writer, source, target).await
     }
 }
 
+async fn push_file(
+    writer: &mut ZipFileWriter<tokio::fs::File>,
+    source: &str,
+    target: &str,
+) -> Result<()> {
+    // 一次读取整个文件, 避免小块读取在阻塞线程池中反复切换
+    let data = tokio::fs::read(source).await?;
+    push_bytes(writer, data.as_slice(), target).await
+}
+
+async fn push_bytes(
+    writer: &mut ZipFileWriter<tokio::fs::File>,
+    data: &[u8],
+    target: &str,
+) -> Result<()> {
+    let builder = ZipEntryBuilder::new(target.into(), async_zip::Compression::Deflate);
+    writer.write_entry_whole(builder, data).await?;
+    Ok(())
+}
+
 #[async_trait]
 impl Exporter for ZipExporter {
     async fn on_cover(&mut self, source: &str, format: &str) -> Result<()> {
@@ -165,8 +199,12 @@ impl Exporter for ZipExporter {
         Ok(())
     }
 
-    async fn on_chapter(&mut self, name: &str) -> Result<()> {
-        let path = allowed_file_name(name);
+    async fn on_chapter(
+        &mut self,
+        chapter: &download_comic_chapter::Model,
+        _page_count: usize,
+    ) -> Result<()> {
+        let path = allowed_file_name(&chapter.name);
         create_dir_if_not_exists(path.as_str());
         let mut lock = self.chaper_folder.lock().await;
         *lock = Some(path);
@@ -174,14 +212,14 @@ impl Exporter for ZipExporter {
         Ok(())
     }
 
-    async fn on_page(&mut self, source: &str, format: &str, index: i32) -> Result<()> {
+    async fn on_page(&mut self, source: &str, page: &download_comic_page::Model) -> Result<()> {
         let chapter_folder = self.chaper_folder.lock().await;
         let cf = chapter_folder
             .as_ref()
             .with_context(|| "chapter folder not found")?;
         let page_path = join_paths(vec![
             cf.as_str(),
-            format!("{:04}.{}", index, format).as_str(),
+            format!("{:04}.{}", page.image_index, page.format).as_str(),
         ]);
         drop(chapter_folder);
         self.push_file(source, page_path.as_str()).await?;
@@ -193,3 +231,621 @@ impl Exporter for ZipExporter {
         Ok(())
     }
 }
+
+// Cbz : 每个章节一个cbz, 放在以漫画命名的文件夹中
+// CbzComic : 整部漫画一个cbz, 章节作为文件夹
+struct CbzExporter {
+    comic: download_comic::Model,
+    comic_folder: String,
+    comic_writer: Option<ZipFileWriter<tokio::fs::File>>,
+    chapter_writer: Option<ZipFileWriter<tokio::fs::File>>,
+    chapter_prefix: String,
+    chapter_seq: usize,
+    page_count: usize,
+}
+
+impl CbzExporter {
+    async fn on_start(
+        export_to_folder: &str,
+        comic: &download_comic::Model,
+        single: bool,
+    ) -> Result<Box<dyn Exporter + Sync + Send>> {
+        let comic_folder = join_paths(vec![
+            export_to_folder,
+            allowed_file_name(comic.name.as_str()).as_str(),
+        ]);
+        let comic_writer = if single {
+            let file = tokio::fs::File::create(format!("{}.cbz", comic_folder).as_str()).await?;
+            Some(ZipFileWriter::with_tokio(file))
+        } else {
+            tokio::fs::create_dir_all(comic_folder.as_str()).await?;
+            None
+        };
+        Ok(Box::new(Self {
+            comic: comic.clone(),
+            comic_folder,
+            comic_writer,
+            chapter_writer: None,
+            chapter_prefix: "".to_owned(),
+            chapter_seq: 0,
+            page_count: 0,
+        }))
+    }
+
+    async fn close_chapter(&mut self) -> Result<()> {
+        if let Some(writer) = self.chapter_writer.take() {
+            writer.close().await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Exporter for CbzExporter {
+    async fn on_cover(&mut self, source: &str, format: &str) -> Result<()> {
+        if let Some(writer) = self.comic_writer.as_mut() {
+            push_file(writer, source, format!("0000_cover.{}", format).as_str()).await?;
+        } else {
+            let cover_path = join_paths(vec![
+                self.comic_folder.as_str(),
+                format!("cover.{}", format).as_str(),
+            ]);
+            tokio::fs::copy(source, cover_path.as_str()).await?;
+        }
+        Ok(())
+    }
+
+    async fn on_chapter(
+        &mut self,
+        chapter: &download_comic_chapter::Model,
+        page_count: usize,
+    ) -> Result<()> {
+        self.chapter_seq += 1;
+        self.page_count += page_count;
+        let name = format!(
+            "{:04}_{}",
+            self.chapter_seq,
+            allowed_file_name(chapter.name.as_str())
+        );
+        if self.comic_writer.is_some() {
+            self.chapter_prefix = format!("{}/", name);
+            return Ok(());
+        }
+        self.close_chapter().await?;
+        let path = join_paths(vec![
+            self.comic_folder.as_str(),
+            format!("{}.cbz", name).as_str(),
+        ]);
+        let mut writer = ZipFileWriter::with_tokio(tokio::fs::File::create(path.as_str()).await?);
+        let info = comic_info_xml(&self.comic, Some(chapter), page_count);
+        push_bytes(&mut writer, info.as_bytes(), "ComicInfo.xml").await?;
+        self.chapter_writer = Some(writer);
+        Ok(())
+    }
+
+    async fn on_page(&mut self, source: &str, page: &download_comic_page::Model) -> Result<()> {
+        let target = format!(
+            "{}{:04}.{}",
+            self.chapter_prefix, page.image_index, page.format
+        );
+        let writer = if let Some(writer) = self.comic_writer.as_mut() {
+            writer
+        } else {
+            self.chapter_writer
+                .as_mut()
+                .with_context(|| "chapter archive not found")?
+        };
+        push_file(writer, source, target.as_str()).await
+    }
+
+    async fn finish(mut self: Box<Self>) -> Result<()> {
+        self.close_chapter().await?;
+        if let Some(mut writer) = self.comic_writer.take() {
+            let info = comic_info_xml(&self.comic, None, self.page_count);
+            push_bytes(&mut writer, info.as_bytes(), "ComicInfo.xml").await?;
+            writer.close().await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+struct Named {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct Classify {
+    display: String,
+}
+
+// download_comic中的author/theme为json数组, region/status为json对象
+fn json_names(value: &str) -> String {
+    serde_json::from_str::<Vec<Named>>(value)
+        .map(|list| list.into_iter().map(|n| n.name).join(", "))
+        .unwrap_or_else(|_| value.to_owned())
+}
+
+fn json_display(value: &str) -> String {
+    serde_json::from_str::<Classify>(value)
+        .map(|classify| classify.display)
+        .unwrap_or_else(|_| value.to_owned())
+}
+
+// ordered为章节序号的十倍, 例如 15 为 1.5 话
+fn chapter_number(ordered: i64) -> String {
+    if ordered % 10 == 0 {
+        (ordered / 10).to_string()
+    } else {
+        format!("{:.1}", ordered as f64 / 10.0)
+    }
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn comic_info_xml(
+    comic: &download_comic::Model,
+    chapter: Option<&download_comic_chapter::Model>,
+    page_count: usize,
+) -> String {
+    let mut fields = vec![];
+    fields.push((
+        "Title",
+        chapter.map_or(comic.name.clone(), |c| c.name.clone()),
+    ));
+    fields.push(("Series", comic.name.clone()));
+    if let Some(chapter) = chapter {
+        fields.push(("Number", chapter_number(chapter.ordered)));
+    }
+    fields.push(("Summary", comic.brief.clone()));
+    fields.push(("Writer", json_names(comic.author.as_str())));
+    fields.push(("Genre", json_names(comic.theme.as_str())));
+    fields.push((
+        "Notes",
+        format!(
+            "{} / {}",
+            json_display(comic.region.as_str()),
+            json_display(comic.status.as_str())
+        ),
+    ));
+    // 导入时从这里取回path_word
+    fields.push(("Web", format!("{}{}", COMIC_WEB_URL, comic.path_word)));
+    fields.push(("PageCount", page_count.to_string()));
+    fields.push(("LanguageISO", "zh".to_owned()));
+    fields.push(("Manga", "Yes".to_owned()));
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<ComicInfo xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" xmlns:xsd=\"http://www.w3.org/2001/XMLSchema\">\n",
+    );
+    for (key, value) in fields {
+        if value.is_empty() {
+            continue;
+        }
+        xml.push_str(format!("  <{}>{}</{}>\n", key, xml_escape(value.as_str()), key).as_str());
+    }
+    xml.push_str("</ComicInfo>\n");
+    xml
+}
+
+struct EpubItem {
+    id: String,
+    href: String,
+    media_type: String,
+    properties: Option<&'static str>,
+}
+
+// 固定版式的EPUB 3, 每张图片一个xhtml页面, viewport使用下载时记录的宽高
+struct EpubExporter {
+    comic: download_comic::Model,
+    writer: ZipFileWriter<tokio::fs::File>,
+    manifest: Vec<EpubItem>,
+    spine: Vec<String>,
+    nav: Vec<(String, String)>,
+    chapter_name: Option<String>,
+    chapter_seq: usize,
+}
+
+impl EpubExporter {
+    async fn on_start(
+        export_to_folder: &str,
+        comic: &download_comic::Model,
+    ) -> Result<Box<dyn Exporter + Sync + Send>> {
+        let path = join_paths(vec![
+            export_to_folder,
+            format!("{}.epub", allowed_file_name(comic.name.as_str())).as_str(),
+        ]);
+        let file = tokio::fs::File::create(path.as_str()).await?;
+        let mut writer = ZipFileWriter::with_tokio(file);
+        // mimetype必须是第一个文件, 且不能压缩
+        writer
+            .write_entry_whole(
+                ZipEntryBuilder::new("mimetype".into(), async_zip::Compression::Stored),
+                b"application/epub+zip",
+            )
+            .await?;
+        push_bytes(
+            &mut writer,
+            EPUB_CONTAINER_XML.as_bytes(),
+            "META-INF/container.xml",
+        )
+        .await?;
+        Ok(Box::new(Self {
+            comic: comic.clone(),
+            writer,
+            manifest: vec![],
+            spine: vec![],
+            nav: vec![],
+            chapter_name: None,
+            chapter_seq: 0,
+        }))
+    }
+
+    async fn push_image_page(
+        &mut self,
+        source: &str,
+        name: &str,
+        format: &str,
+        width: u32,
+        height: u32,
+        properties: Option<&'static str>,
+    ) -> Result<String> {
+        let image_href = format!("images/{}.{}", name, format);
+        push_file(
+            &mut self.writer,
+            source,
+            format!("OEBPS/{}", image_href).as_str(),
+        )
+        .await?;
+        self.manifest.push(EpubItem {
+            id: format!("img_{}", name),
+            href: image_href.clone(),
+            media_type: image_media_type(format).to_owned(),
+            properties,
+        });
+        let page_href = format!("pages/{}.xhtml", name);
+        let page = epub_page_xhtml(self.comic.name.as_str(), &image_href, width, height);
+        push_bytes(
+            &mut self.writer,
+            page.as_bytes(),
+            format!("OEBPS/{}", page_href).as_str(),
+        )
+        .await?;
+        let page_id = format!("page_{}", name);
+        self.manifest.push(EpubItem {
+            id: page_id.clone(),
+            href: page_href.clone(),
+            media_type: "application/xhtml+xml".to_owned(),
+            properties: None,
+        });
+        self.spine.push(page_id);
+        Ok(page_href)
+    }
+}
+
+#[async_trait]
+impl Exporter for EpubExporter {
+    async fn on_cover(&mut self, source: &str, format: &str) -> Result<()> {
+        let (width, height) = (self.comic.cover_width, self.comic.cover_height);
+        let href = self
+            .push_image_page(source, "cover", format, width, height, Some("cover-image"))
+            .await?;
+        self.nav.push(("封面".to_owned(), href));
+        Ok(())
+    }
+
+    async fn on_chapter(
+        &mut self,
+        chapter: &download_comic_chapter::Model,
+        _page_count: usize,
+    ) -> Result<()> {
+        self.chapter_seq += 1;
+        self.chapter_name = Some(chapter.name.clone());
+        Ok(())
+    }
+
+    async fn on_page(&mut self, source: &str, page: &download_comic_page::Model) -> Result<()> {
+        let name = format!("c{:04}_p{:04}", self.chapter_seq, page.image_index);
+        let href = self
+            .push_image_page(
+                source,
+                name.as_str(),
+                page.format.as_str(),
+                page.width,
+                page.height,
+                None,
+            )
+            .await?;
+        // 章节的第一页作为目录入口
+        if let Some(chapter_name) = self.chapter_name.take() {
+            self.nav.push((chapter_name, href));
+        }
+        Ok(())
+    }
+
+    async fn finish(mut self: Box<Self>) -> Result<()> {
+        let nav = epub_nav_xhtml(self.comic.name.as_str(), &self.nav);
+        push_bytes(&mut self.writer, nav.as_bytes(), "OEBPS/nav.xhtml").await?;
+        self.manifest.push(EpubItem {
+            id: "nav".to_owned(),
+            href: "nav.xhtml".to_owned(),
+            media_type: "application/xhtml+xml".to_owned(),
+            properties: Some("nav"),
+        });
+        let opf = epub_opf(&self.comic, &self.manifest, &self.spine);
+        push_bytes(&mut self.writer, opf.as_bytes(), "OEBPS/content.opf").await?;
+        self.writer.close().await?;
+        Ok(())
+    }
+}
+
+const COMIC_WEB_URL: &str = "https://www.mangacopy.com/comic/";
+
+const EPUB_CONTAINER_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>
+"#;
+
+fn image_media_type(format: &str) -> &'static str {
+    match format {
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "bmp" => "image/bmp",
+        _ => "application/octet-stream",
+    }
+}
+
+fn epub_page_xhtml(title: &str, image_href: &str, width: u32, height: u32) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops">
+<head>
+  <title>{title}</title>
+  <meta name="viewport" content="width={width}, height={height}"/>
+  <style>html, body {{ margin: 0; padding: 0; }} img {{ width: {width}px; height: {height}px; }}</style>
+</head>
+<body>
+  <img src="../{image_href}" alt=""/>
+</body>
+</html>
+"#,
+        title = xml_escape(title),
+        image_href = xml_escape(image_href),
+    )
+}
+
+fn epub_nav_xhtml(title: &str, nav: &[(String, String)]) -> String {
+    let items = nav
+        .iter()
+        .map(|(name, href)| {
+            format!(
+                "      <li><a href=\"{}\">{}</a></li>\n",
+                xml_escape(href),
+                xml_escape(name)
+            )
+        })
+        .join("");
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops">
+<head>
+  <title>{title}</title>
+</head>
+<body>
+  <nav epub:type="toc" id="toc">
+    <ol>
+{items}    </ol>
+  </nav>
+</body>
+</html>
+"#,
+        title = xml_escape(title),
+    )
+}
+
+fn epub_opf(comic: &download_comic::Model, manifest: &[EpubItem], spine: &[String]) -> String {
+    let mut metadata = vec![
+        format!(
+            "    <dc:identifier id=\"book-id\">urn:uuid:{}</dc:identifier>",
+            xml_escape(comic.uuid.as_str())
+        ),
+        format!(
+            "    <dc:title>{}</dc:title>",
+            xml_escape(comic.name.as_str())
+        ),
+        "    <dc:language>zh</dc:language>".to_owned(),
+    ];
+    let authors = json_names(comic.author.as_str());
+    if !authors.is_empty() {
+        metadata.push(format!(
+            "    <dc:creator>{}</dc:creator>",
+            xml_escape(authors.as_str())
+        ));
+    }
+    if !comic.brief.is_empty() {
+        metadata.push(format!(
+            "    <dc:description>{}</dc:description>",
+            xml_escape(comic.brief.as_str())
+        ));
+    }
+    let themes = json_names(comic.theme.as_str());
+    if !themes.is_empty() {
+        metadata.push(format!(
+            "    <dc:subject>{}</dc:subject>",
+            xml_escape(themes.as_str())
+        ));
+    }
+    metadata.push(format!(
+        "    <meta property=\"dcterms:modified\">{}</meta>",
+        chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ")
+    ));
+    metadata.push("    <meta property=\"rendition:layout\">pre-paginated</meta>".to_owned());
+    metadata.push("    <meta property=\"rendition:spread\">none</meta>".to_owned());
+    metadata.push("    <meta name=\"cover\" content=\"img_cover\"/>".to_owned());
+    let manifest = manifest
+        .iter()
+        .map(|item| {
+            format!(
+                "    <item id=\"{}\" href=\"{}\" media-type=\"{}\"{}/>",
+                item.id,
+                xml_escape(item.href.as_str()),
+                item.media_type,
+                item.properties
+                    .map(|p| format!(" properties=\"{}\"", p))
+                    .unwrap_or_default()
+            )
+        })
+        .join("\n");
+    let spine = spine
+        .iter()
+        .map(|id| format!("    <itemref idref=\"{}\"/>", id))
+        .join("\n");
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="book-id" prefix="rendition: http://www.idpf.org/vocab/rendition/#">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+{metadata}
+  </metadata>
+  <manifest>
+{manifest}
+  </manifest>
+  <spine>
+{spine}
+  </spine>
+</package>
+"#,
+        metadata = metadata.join("\n"),
+    )
+}
+
+// 每部漫画一个PDF, 页面大小为图片的像素大小, 每个章节的第一页作为书签
+// 读取图片, 转码和写文件都在spawn_blocking中进行, 避免阻塞下载等异步任务
+struct PdfExporter {
+    writer: Option<PdfWriter<std::io::BufWriter<std::fs::File>>>,
+    chapter_name: Option<String>,
+}
+
+impl PdfExporter {
+    async fn on_start(
+        export_to_folder: &str,
+        comic: &download_comic::Model,
+    ) -> Result<Box<dyn Exporter + Sync + Send>> {
+        let path = join_paths(vec![
+            export_to_folder,
+            format!("{}.pdf", allowed_file_name(comic.name.as_str())).as_str(),
+        ]);
+        let file = tokio::fs::File::create(path.as_str())
+            .await?
+            .into_std()
+            .await;
+        let writer =
+            tokio::task::spawn_blocking(move || PdfWriter::new(std::io::BufWriter::new(file)))
+                .await??;
+        Ok(Box::new(Self {
+            writer: Some(writer),
+            chapter_name: None,
+        }))
+    }
+
+    async fn add_page(&mut self, source: &str, format: &str) -> Result<usize> {
+        let mut writer = self.writer.take().with_context(|| "pdf writer closed")?;
+        let source = source.to_owned();
+        let format = format.to_owned();
+        let (writer, page_index) = tokio::task::spawn_blocking(move || {
+            let image = pdf_jpeg(source.as_str(), format.as_str())?;
+            let page_index =
+                writer.add_jpeg_page(&image.data, image.width, image.height, image.gray)?;
+            Ok((writer, page_index))
+        })
+        .await??;
+        self.writer = Some(writer);
+        Ok(page_index)
+    }
+}
+
+#[async_trait]
+impl Exporter for PdfExporter {
+    async fn on_cover(&mut self, source: &str, format: &str) -> Result<()> {
+        let page_index = self.add_page(source, format).await?;
+        if let Some(writer) = self.writer.as_mut() {
+            writer.add_bookmark("封面", page_index);
+        }
+        Ok(())
+    }
+
+    async fn on_chapter(
+        &mut self,
+        chapter: &download_comic_chapter::Model,
+        _page_count: usize,
+    ) -> Result<()> {
+        self.chapter_name = Some(chapter.name.clone());
+        Ok(())
+    }
+
+    async fn on_page(&mut self, source: &str, page: &download_comic_page::Model) -> Result<()> {
+        let page_index = self.add_page(source, page.format.as_str()).await?;
+        if let Some(chapter_name) = self.chapter_name.take() {
+            if let Some(writer) = self.writer.as_mut() {
+                writer.add_bookmark(chapter_name.as_str(), page_index);
+            }
+        }
+        Ok(())
+    }
+
+    async fn finish(mut self: Box<Self>) -> Result<()> {
+        let writer = self.writer.take().with_context(|| "pdf writer closed")?;
+        tokio::task::spawn_blocking(move || writer.finish()).await??;
+        Ok(())
+    }
+}
+
+struct PdfJpeg {
+    data: Vec<u8>,
+    gray: bool,
+    width: u32,
+    height: u32,
+}
+
+// RGB和灰度的JPEG直接嵌入, 其他图片(webp/png/gif/CMYK的JPEG等)转码为JPEG,
+// 宽高取自实际嵌入的图片
+fn pdf_jpeg(source: &str, format: &str) -> Result<PdfJpeg> {
+    let data = std::fs::read(source).with_context(|| format!("read image {}", source))?;
+    if format == "jpg" || format == "jpeg" {
+        let decoder = image::codecs::jpeg::JpegDecoder::new(std::io::Cursor::new(&data))?;
+        let (width, height) = decoder.dimensions();
+        let gray = match decoder.original_color_type() {
+            image::ExtendedColorType::L8 => Some(true),
+            image::ExtendedColorType::Rgb8 => Some(false),
+            _ => None,
+        };
+        if let Some(gray) = gray {
+            return Ok(PdfJpeg {
+                data,
+                gray,
+                width,
+                height,
+            });
+        }
+    }
+    let image = image::load_from_memory(&data)?.to_rgb8();
+    let mut jpeg = vec![];
+    image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg, 90).encode_image(&image)?;
+    Ok(PdfJpeg {
+        data: jpeg,
+        gray: false,
+        width: image.width(),
+        height: image.height(),
+    })
+}
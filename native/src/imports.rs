@@ -0,0 +1,690 @@
+use crate::database::download::{
+    self, download_comic, download_comic_chapter, download_comic_group, download_comic_page,
+};
+use crate::get_download_dir;
+use crate::utils::join_paths;
+use anyhow::{Context, Result};
+use async_zip::tokio::read::fs::ZipFileReader;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::path::Path;
+
+const IMAGE_EXTENSIONS: [&str; 6] = ["jpg", "jpeg", "png", "webp", "gif", "bmp"];
+const COMIC_INFO_FIELDS: [&str; 8] = [
+    "Title", "Series", "Number", "Summary", "Writer", "Genre", "Notes", "Web",
+];
+const DEFAULT_GROUP_PATH_WORD: &str = "default";
+const DEFAULT_GROUP_NAME: &str = "默认";
+
+// 图片的来源: 文件, 或者压缩包中的第几个条目
+enum ImportSource {
+    File(String),
+    Entry(String, usize),
+}
+
+struct ImportChapter {
+    name: String,
+    number: Option<String>,
+    pages: Vec<ImportSource>,
+}
+
+struct ImportComic {
+    name: String,
+    info: HashMap<String, String>,
+    cover: Option<ImportSource>,
+    chapters: Vec<ImportChapter>,
+}
+
+struct ImportModels {
+    comic: download_comic::Model,
+    groups: Vec<download_comic_group::Model>,
+    chapters: Vec<download_comic_chapter::Model>,
+    pages: Vec<download_comic_page::Model>,
+}
+
+// 导入Folder/Zip/Cbz/CbzComic导出的漫画, 返回导入后的path_word
+pub(crate) async fn imports(paths: Vec<String>) -> Result<Vec<String>> {
+    let mut path_words = vec![];
+    for path in &paths {
+        let comic = scan(path)
+            .await
+            .with_context(|| format!("import {}", path))?;
+        path_words.push(import_comic(comic).await?);
+    }
+    Ok(path_words)
+}
+
+async fn scan(path: &str) -> Result<ImportComic> {
+    let p = Path::new(path);
+    if tokio::fs::metadata(path).await?.is_dir() {
+        let name = file_name(path);
+        let files = list_dir(path).await?;
+        if files
+            .iter()
+            .any(|(file, is_dir)| !is_dir && extension(file) == "cbz")
+        {
+            scan_cbz_folder(name, files).await
+        } else {
+            scan_folder(name, files).await
+        }
+    } else {
+        let name = p
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        match extension(path).as_str() {
+            "zip" => scan_archive(path, name, false).await,
+            "cbz" => scan_archive(path, name, true).await,
+            _ => Err(anyhow::anyhow!("unknown imports type")),
+        }
+    }
+}
+
+// Folder : 漫画文件夹/cover.jpg, 漫画文件夹/章节/0000.jpg
+async fn scan_folder(name: String, files: Vec<(String, bool)>) -> Result<ImportComic> {
+    let mut comic = ImportComic {
+        name,
+        info: HashMap::new(),
+        cover: None,
+        chapters: vec![],
+    };
+    for (file, is_dir) in files {
+        if is_dir {
+            let pages = list_dir(file.as_str())
+                .await?
+                .into_iter()
+                .filter(|(page, is_dir)| !is_dir && is_image(page))
+                .map(|(page, _)| ImportSource::File(page))
+                .collect::<Vec<_>>();
+            comic.chapters.push(ImportChapter {
+                name: file_name(file.as_str()),
+                number: None,
+                pages,
+            });
+        } else if is_cover(file.as_str()) {
+            comic.cover = Some(ImportSource::File(file));
+        }
+    }
+    Ok(comic)
+}
+
+// Cbz : 漫画文件夹/cover.jpg, 漫画文件夹/0001_章节.cbz
+async fn scan_cbz_folder(name: String, files: Vec<(String, bool)>) -> Result<ImportComic> {
+    let mut comic = ImportComic {
+        name,
+        info: HashMap::new(),
+        cover: None,
+        chapters: vec![],
+    };
+    for (file, is_dir) in files {
+        if is_dir {
+            continue;
+        }
+        if extension(file.as_str()) == "cbz" {
+            let reader = ZipFileReader::new(file.as_str()).await?;
+            let mut info = HashMap::new();
+            let mut pages = vec![];
+            for (index, entry) in archive_entries(&reader) {
+                if entry == "ComicInfo.xml" {
+                    info = parse_comic_info(read_entry(&reader, index).await?.as_slice());
+                } else if is_image(entry.as_str()) {
+                    pages.push((entry, index));
+                }
+            }
+            pages.sort_by(|a, b| natural_cmp(a.0.as_str(), b.0.as_str()));
+            let stem = Path::new(file.as_str())
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default();
+            comic.chapters.push(ImportChapter {
+                name: info
+                    .get("Title")
+                    .cloned()
+                    .unwrap_or_else(|| strip_seq(stem.as_str())),
+                number: info.get("Number").cloned(),
+                pages: pages
+                    .into_iter()
+                    .map(|(_, index)| ImportSource::Entry(file.clone(), index))
+                    .collect(),
+            });
+            if comic.info.is_empty() {
+                comic.info = info;
+            }
+        } else if is_cover(file.as_str()) {
+            comic.cover = Some(ImportSource::File(file));
+        }
+    }
+    Ok(comic)
+}
+
+// Zip : cover.jpg, 章节/0000.jpg
+// CbzComic : ComicInfo.xml, 0000_cover.jpg, 0001_章节/0000.jpg
+// 单章节的cbz : ComicInfo.xml, 0000.jpg
+async fn scan_archive(path: &str, name: String, cbz: bool) -> Result<ImportComic> {
+    let reader = ZipFileReader::new(path).await?;
+    let mut comic = ImportComic {
+        name,
+        info: HashMap::new(),
+        cover: None,
+        chapters: vec![],
+    };
+    let mut chapters: Vec<(String, Vec<(String, usize)>)> = vec![];
+    let mut root_pages = vec![];
+    for (index, entry) in archive_entries(&reader) {
+        match entry.rsplit_once('/') {
+            Some((folder, file)) => {
+                if !is_image(file) {
+                    continue;
+                }
+                let position = match chapters.iter().position(|(name, _)| name == folder) {
+                    Some(position) => position,
+                    None => {
+                        chapters.push((folder.to_owned(), vec![]));
+                        chapters.len() - 1
+                    }
+                };
+                chapters[position].1.push((entry.clone(), index));
+            }
+            None => {
+                if entry == "ComicInfo.xml" {
+                    comic.info = parse_comic_info(read_entry(&reader, index).await?.as_slice());
+                } else if is_cover(entry.as_str()) {
+                    comic.cover = Some(ImportSource::Entry(path.to_owned(), index));
+                } else if is_image(entry.as_str()) {
+                    root_pages.push((entry, index));
+                }
+            }
+        }
+    }
+    if !root_pages.is_empty() {
+        // 单章节的cbz, ComicInfo中的Title为章节名
+        root_pages.sort_by(|a, b| natural_cmp(a.0.as_str(), b.0.as_str()));
+        comic.chapters.push(ImportChapter {
+            name: comic
+                .info
+                .get("Title")
+                .cloned()
+                .unwrap_or_else(|| comic.name.clone()),
+            number: comic.info.get("Number").cloned(),
+            pages: root_pages
+                .into_iter()
+                .map(|(_, index)| ImportSource::Entry(path.to_owned(), index))
+                .collect(),
+        });
+    }
+    for (name, mut pages) in chapters {
+        pages.sort_by(|a, b| natural_cmp(a.0.as_str(), b.0.as_str()));
+        comic.chapters.push(ImportChapter {
+            name: if cbz {
+                strip_seq(file_name(name.as_str()).as_str())
+            } else {
+                file_name(name.as_str())
+            },
+            number: None,
+            pages: pages
+                .into_iter()
+                .map(|(_, index)| ImportSource::Entry(path.to_owned(), index))
+                .collect(),
+        });
+    }
+    Ok(comic)
+}
+
+async fn import_comic(comic: ImportComic) -> Result<String> {
+    if comic
+        .chapters
+        .iter()
+        .all(|chapter| chapter.pages.is_empty())
+    {
+        return Err(anyhow::anyhow!("no images found"));
+    }
+    let name = comic
+        .info
+        .get("Series")
+        .cloned()
+        .unwrap_or(comic.name.clone());
+    let uuid = hex::encode(md5::compute(name.as_bytes()).as_slice());
+    // 优先使用导出时写入的path_word, 其他来源的漫画按名称生成
+    let path_word = embedded_path_word(&comic.info).unwrap_or_else(|| format!("import_{}", uuid));
+    if download_comic::find_by_path_word(path_word.as_str())
+        .await?
+        .is_some()
+    {
+        // 已经在下载列表中, 不覆盖已有的下载
+        log::info!("import skipped, comic already downloaded : {}", path_word);
+        return Ok(path_word);
+    }
+    let download_dir = get_download_dir();
+    let comic_dir = join_paths(vec![download_dir.as_str(), path_word.as_str()]);
+    // 先写入临时文件夹, 完成后再改名, 失败时不留下不完整的文件夹
+    let temp_dir = join_paths(vec![
+        download_dir.as_str(),
+        format!("{}.importing", path_word).as_str(),
+    ]);
+    remove_dir_if_exists(temp_dir.as_str()).await?;
+    tokio::fs::create_dir_all(temp_dir.as_str()).await?;
+    let models = match write_comic(comic, name, uuid, path_word.as_str(), temp_dir.as_str()).await {
+        Ok(models) => models,
+        Err(err) => {
+            let _ = tokio::fs::remove_dir_all(temp_dir.as_str()).await;
+            return Err(err);
+        }
+    };
+    // 没有数据库记录的残留文件夹
+    remove_dir_if_exists(comic_dir.as_str()).await?;
+    tokio::fs::rename(temp_dir.as_str(), comic_dir.as_str()).await?;
+    if let Err(err) =
+        download::import_comic(models.comic, models.groups, models.chapters, models.pages).await
+    {
+        let _ = tokio::fs::remove_dir_all(comic_dir.as_str()).await;
+        return Err(err);
+    }
+    Ok(path_word)
+}
+
+async fn write_comic(
+    comic: ImportComic,
+    name: String,
+    uuid: String,
+    path_word: &str,
+    comic_dir: &str,
+) -> Result<ImportModels> {
+    let mut reader: Option<(String, ZipFileReader)> = None;
+    let now = chrono::Local::now();
+    let date = now.format("%Y-%m-%d").to_string();
+    // 没有封面时使用第一张图片
+    let cover_source = comic.cover.as_ref().or_else(|| {
+        comic
+            .chapters
+            .iter()
+            .find_map(|chapter| chapter.pages.first())
+    });
+    let (cover_format, cover_width, cover_height) = match cover_source {
+        Some(source) => {
+            let data = read_source(&mut reader, source).await?;
+            let info = image_info(data.as_slice())?;
+            let path = join_paths(vec![comic_dir, "cover"]);
+            tokio::fs::write(path.as_str(), data).await?;
+            info
+        }
+        None => ("".to_owned(), 0, 0),
+    };
+    let chapter_uuids = comic
+        .chapters
+        .iter()
+        .enumerate()
+        .map(|(idx, chapter)| {
+            hex::encode(
+                md5::compute(format!("{}${}${}", path_word, idx, chapter.name).as_bytes())
+                    .as_slice(),
+            )
+        })
+        .collect::<Vec<_>>();
+    let mut chapters = vec![];
+    let mut pages = vec![];
+    for (idx, chapter) in comic.chapters.iter().enumerate() {
+        let chapter_uuid = chapter_uuids[idx].clone();
+        let chapter_dir = join_paths(vec![comic_dir, chapter_uuid.as_str()]);
+        tokio::fs::create_dir_all(chapter_dir.as_str()).await?;
+        for (image_index, source) in chapter.pages.iter().enumerate() {
+            let data = read_source(&mut reader, source).await?;
+            let (format, width, height) = image_info(data.as_slice())?;
+            let path = join_paths(vec![chapter_dir.as_str(), image_index.to_string().as_str()]);
+            tokio::fs::write(path.as_str(), data).await?;
+            pages.push(download_comic_page::Model {
+                comic_path_word: path_word.to_owned(),
+                chapter_uuid: chapter_uuid.clone(),
+                image_index: image_index as i32,
+                url: "".to_owned(),
+                cache_key: format!("/import/{}/{}/{}", path_word, chapter_uuid, image_index),
+                download_status: download_comic_page::STATUS_DOWNLOAD_SUCCESS,
+                width,
+                height,
+                format,
+            });
+        }
+        let ordered = chapter
+            .number
+            .as_ref()
+            .and_then(|number| number.parse::<f64>().ok())
+            .map(|number| (number * 10.0).round() as i64)
+            .unwrap_or((idx as i64 + 1) * 10);
+        chapters.push(download_comic_chapter::Model {
+            comic_path_word: path_word.to_owned(),
+            uuid: chapter_uuid,
+            comic_id: uuid.clone(),
+            count: comic.chapters.len() as i64,
+            datetime_created: date.clone(),
+            group_path_word: DEFAULT_GROUP_PATH_WORD.to_owned(),
+            img_type: 0,
+            index: idx as i64,
+            is_long: false,
+            name: chapter.name.clone(),
+            news: "".to_owned(),
+            next: chapter_uuids.get(idx + 1).cloned(),
+            ordered,
+            prev: if idx > 0 {
+                Some(chapter_uuids[idx - 1].clone())
+            } else {
+                None
+            },
+            size: chapter.pages.len() as i64,
+            type_field: 1,
+            download_status: download_comic_chapter::STATUS_FETCH_SUCCESS,
+        });
+    }
+    let (region, status) = comic
+        .info
+        .get("Notes")
+        .and_then(|notes| notes.split_once(" / "))
+        .map(|(region, status)| (region.to_owned(), status.to_owned()))
+        .unwrap_or_default();
+    let image_count = pages.len() as i64;
+    let model = download_comic::Model {
+        path_word: path_word.to_owned(),
+        alias: None,
+        author: json_named_list(comic.info.get("Writer"), true),
+        b_404: false,
+        b_hidden: false,
+        ban: 0,
+        brief: comic.info.get("Summary").cloned().unwrap_or_default(),
+        close_comment: true,
+        close_roast: true,
+        cover: "".to_owned(),
+        datetime_updated: date,
+        females: "[]".to_owned(),
+        free_type: json_classify(""),
+        img_type: 0,
+        males: "[]".to_owned(),
+        name,
+        popular: 0,
+        reclass: json_classify(""),
+        region: json_classify(region.as_str()),
+        restrict: json_classify(""),
+        seo_baidu: "".to_owned(),
+        status: json_classify(status.as_str()),
+        theme: json_named_list(comic.info.get("Genre"), false),
+        uuid,
+        append_time: now.timestamp(),
+        cover_cache_key: format!("/import/{}/cover", path_word),
+        cover_download_status: if cover_format.is_empty() {
+            download_comic::STATUS_DOWNLOAD_FAILED
+        } else {
+            download_comic::STATUS_DOWNLOAD_SUCCESS
+        },
+        cover_format,
+        cover_width,
+        cover_height,
+        image_count,
+        image_count_success: image_count,
+        download_status: download_comic::STATUS_DOWNLOAD_SUCCESS,
+    };
+    let group = download_comic_group::Model {
+        comic_path_word: path_word.to_owned(),
+        group_path_word: DEFAULT_GROUP_PATH_WORD.to_owned(),
+        count: chapters.len() as i64,
+        name: DEFAULT_GROUP_NAME.to_owned(),
+        group_rank: 0,
+    };
+    Ok(ImportModels {
+        comic: model,
+        groups: vec![group],
+        chapters,
+        pages,
+    })
+}
+
+async fn read_source(
+    reader: &mut Option<(String, ZipFileReader)>,
+    source: &ImportSource,
+) -> Result<Vec<u8>> {
+    match source {
+        ImportSource::File(path) => Ok(tokio::fs::read(path).await?),
+        ImportSource::Entry(archive, index) => {
+            // 按顺序读取, 只保留最后打开的压缩包
+            if reader.as_ref().map(|(path, _)| path) != Some(archive) {
+                *reader = Some((archive.clone(), ZipFileReader::new(archive).await?));
+            }
+            let (_, zip) = reader.as_ref().with_context(|| "archive not opened")?;
+            read_entry(zip, *index).await
+        }
+    }
+}
+
+fn archive_entries(reader: &ZipFileReader) -> Vec<(usize, String)> {
+    reader
+        .file()
+        .entries()
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            (
+                index,
+                String::from_utf8_lossy(entry.filename().as_bytes()).replace('\\', "/"),
+            )
+        })
+        .filter(|(_, name)| !name.ends_with('/'))
+        .collect()
+}
+
+async fn read_entry(reader: &ZipFileReader, index: usize) -> Result<Vec<u8>> {
+    let mut entry = reader.reader_with_entry(index).await?;
+    let mut data = vec![];
+    entry.read_to_end_checked(&mut data).await?;
+    Ok(data)
+}
+
+fn image_info(data: &[u8]) -> Result<(String, u32, u32)> {
+    let format = image::guess_format(data)?;
+    let (width, height) =
+        image::ImageReader::with_format(std::io::Cursor::new(data), format).into_dimensions()?;
+    let format = format
+        .extensions_str()
+        .first()
+        .map(|format| format.to_string())
+        .unwrap_or_default();
+    Ok((format, width, height))
+}
+
+// 文件夹中的文件(完整路径, 是否为文件夹), 按自然顺序排序
+async fn list_dir(path: &str) -> Result<Vec<(String, bool)>> {
+    let mut files = vec![];
+    let mut entries = tokio::fs::read_dir(path).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        files.push((
+            entry.path().to_string_lossy().to_string(),
+            entry.file_type().await?.is_dir(),
+        ));
+    }
+    files.sort_by(|a, b| natural_cmp(a.0.as_str(), b.0.as_str()));
+    Ok(files)
+}
+
+async fn remove_dir_if_exists(path: &str) -> Result<()> {
+    if tokio::fs::try_exists(path).await? {
+        tokio::fs::remove_dir_all(path).await?;
+    }
+    Ok(())
+}
+
+// 导出时写入的 Web: https://.../comic/{path_word}
+fn embedded_path_word(info: &HashMap<String, String>) -> Option<String> {
+    info.get("Web")
+        .and_then(|web| web.rsplit_once("/comic/"))
+        .map(|(_, path_word)| path_word.trim_end_matches('/'))
+        .filter(|path_word| {
+            !path_word.is_empty()
+                && path_word
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        })
+        .map(|path_word| path_word.to_owned())
+}
+
+fn file_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn extension(path: &str) -> String {
+    Path::new(path)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+fn is_image(path: &str) -> bool {
+    IMAGE_EXTENSIONS.contains(&extension(path).as_str())
+}
+
+fn is_cover(path: &str) -> bool {
+    let stem = Path::new(path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    is_image(path) && (stem == "cover" || stem == "0000_cover")
+}
+
+// 去掉Cbz导出时添加的章节序号, 例如 0001_第1话
+fn strip_seq(name: &str) -> String {
+    match name.split_once('_') {
+        Some((seq, rest)) if !seq.is_empty() && seq.chars().all(|c| c.is_ascii_digit()) => {
+            rest.to_owned()
+        }
+        _ => name.to_owned(),
+    }
+}
+
+// 数字按数值比较, 使 第2话 排在 第10话 之前
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a = a.chars().peekable();
+    let mut b = b.chars().peekable();
+    loop {
+        match (a.peek().copied(), b.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let mut x_num = String::new();
+                while let Some(c) = a.next_if(|c| c.is_ascii_digit()) {
+                    x_num.push(c);
+                }
+                let mut y_num = String::new();
+                while let Some(c) = b.next_if(|c| c.is_ascii_digit()) {
+                    y_num.push(c);
+                }
+                let x_trim = x_num.trim_start_matches('0');
+                let y_trim = y_num.trim_start_matches('0');
+                let ordering = x_trim
+                    .len()
+                    .cmp(&y_trim.len())
+                    .then_with(|| x_trim.cmp(y_trim));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(x), Some(y)) => {
+                if x != y {
+                    return x.cmp(&y);
+                }
+                a.next();
+                b.next();
+            }
+        }
+    }
+}
+
+// 只读取导出时写入的字段
+fn parse_comic_info(data: &[u8]) -> HashMap<String, String> {
+    let xml = String::from_utf8_lossy(data);
+    let mut info = HashMap::new();
+    for key in COMIC_INFO_FIELDS {
+        let start = format!("<{}>", key);
+        let end = format!("</{}>", key);
+        if let Some(begin) = xml.find(start.as_str()) {
+            let value = &xml[begin + start.len()..];
+            if let Some(finish) = value.find(end.as_str()) {
+                info.insert(key.to_owned(), xml_unescape(&value[..finish]));
+            }
+        }
+    }
+    info
+}
+
+fn xml_unescape(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+// 与下载时保存的json格式一致, author带有alias字段
+fn json_named_list(names: Option<&String>, alias: bool) -> String {
+    let list = names
+        .map(|names| {
+            names
+                .split(", ")
+                .filter(|name| !name.is_empty())
+                .map(|name| {
+                    if alias {
+                        serde_json::json!({"name": name, "alias": null, "path_word": ""})
+                    } else {
+                        serde_json::json!({"name": name, "path_word": ""})
+                    }
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    serde_json::Value::Array(list).to_string()
+}
+
+fn json_classify(display: &str) -> String {
+    serde_json::json!({"display": display, "value": 0}).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_exported_comic_info() {
+        let xml = "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<ComicInfo>\n  <Title>第1话</Title>\n  <Series>A &amp; B</Series>\n  <Number>1.5</Number>\n  <Web>https://www.mangacopy.com/comic/a_b</Web>\n  <PageCount>3</PageCount>\n</ComicInfo>\n";
+        let info = parse_comic_info(xml.as_bytes());
+        assert_eq!(info.get("Title").map(String::as_str), Some("第1话"));
+        assert_eq!(info.get("Series").map(String::as_str), Some("A & B"));
+        assert_eq!(info.get("Number").map(String::as_str), Some("1.5"));
+        assert_eq!(info.get("Summary"), None);
+        assert_eq!(info.get("PageCount"), None);
+        assert_eq!(embedded_path_word(&info).as_deref(), Some("a_b"));
+    }
+
+    #[test]
+    fn embedded_path_word_rejects_unsafe_names() {
+        let mut info = HashMap::new();
+        info.insert(
+            "Web".to_owned(),
+            "https://example.com/comic/../x".to_owned(),
+        );
+        assert_eq!(embedded_path_word(&info), None);
+        info.insert("Web".to_owned(), "https://example.com/".to_owned());
+        assert_eq!(embedded_path_word(&info), None);
+    }
+
+    #[test]
+    fn natural_order() {
+        assert_eq!(natural_cmp("第2话", "第10话"), Ordering::Less);
+        assert_eq!(natural_cmp("0010", "9"), Ordering::Greater);
+        assert_eq!(natural_cmp("02", "2"), Ordering::Equal);
+        assert_eq!(natural_cmp("a", "b"), Ordering::Less);
+    }
+
+    #[test]
+    fn strip_export_seq() {
+        assert_eq!(strip_seq("0001_第1话"), "第1话");
+        assert_eq!(strip_seq("第1话_上"), "第1话_上");
+    }
+}
@@ -1,6 +1,8 @@
 pub mod api;
+mod auth_event;
+mod author_follow;
 use crate::database::init_database;
-use crate::database::properties::property;
+use crate::database::properties::{property, secret};
 use base64::Engine;
 use copy_client::Client;
 use lazy_static::lazy_static;
@@ -9,10 +11,19 @@ use std::sync::Arc;
 use tokio::sync::Mutex;
 use utils::create_dir_if_not_exists;
 use utils::join_paths;
+mod browse_sync;
 pub mod copy_client;
 mod database;
+mod download_event;
 pub mod downloading;
 mod exports;
+mod imports;
+mod logging;
+mod offline;
+mod pdf_writer;
+mod profile;
+mod rate_limit;
+mod subscription;
 mod udto;
 mod utils;
 
@@ -47,8 +58,12 @@ pub async fn init_root(path: &str) {
         return;
     }
     *lock = true;
-    println!("Init application with root : {}", path);
     ROOT.set(path.to_owned()).unwrap();
+    logging::init(
+        join_paths(vec![path, "logs"]).as_str(),
+        log::LevelFilter::Info,
+    );
+    log::info!("Init application with root : {}", path);
     IMAGE_CACHE_DIR
         .set(join_paths(vec![path, "image_cache"]))
         .unwrap();
@@ -63,18 +78,51 @@ pub async fn init_root(path: &str) {
     create_dir_if_not_exists(DATABASE_DIR.get().unwrap());
     create_dir_if_not_exists(DOWNLOAD_DIR.get().unwrap());
     init_database().await;
+    if let Some(level) = logging::parse_level(
+        property::load_property("log_level".to_owned())
+            .await
+            .unwrap()
+            .as_str(),
+    ) {
+        logging::set_level(level);
+    }
     reset_api().await;
     load_api().await;
+    profile::load().await;
     init_device().await;
+    init_credentials().await;
+    init_retry_policy().await;
+    CLIENT.set_offline(
+        property::load_property("offline_mode".to_owned())
+            .await
+            .unwrap()
+            == "true",
+    );
+    if let Some(policy) = database::cache::web_cache::CachePolicy::parse(
+        property::load_property("cache_policy".to_owned())
+            .await
+            .unwrap()
+            .as_str(),
+    ) {
+        *database::cache::web_cache::CACHE_POLICY.lock().await = policy;
+    }
     *downloading::DOWNLOAD_AND_EXPORT_TO.lock().await =
         database::properties::property::load_property("download_and_export_to".to_owned())
             .await
             .unwrap();
+    let download_and_export_type =
+        database::properties::property::load_property("download_and_export_type".to_owned())
+            .await
+            .unwrap();
+    if !download_and_export_type.is_empty() {
+        *downloading::DOWNLOAD_AND_EXPORT_TYPE.lock().await = download_and_export_type;
+    }
     *downloading::PAUSE_FLAG.lock().await =
         database::properties::property::load_property("download_pause".to_owned())
             .await
             .unwrap()
             == "true";
+    downloading::load_download_settings().await.unwrap();
     tokio::spawn(downloading::start_download());
 }
 
@@ -134,27 +182,100 @@ async fn reset_api() {
 
 async fn load_api() {
     let api = property::load_property("api".to_owned()).await.unwrap();
-    if api.is_empty() {
-        return;
+    if !api.is_empty() {
+        CLIENT.set_api_host(api).await;
+    }
+    // 镜像列表, 包含当前使用的和内置的地址
+    let mut api_hosts: Vec<String> = serde_json::from_str(
+        property::load_property("api_hosts".to_owned())
+            .await
+            .unwrap()
+            .as_str(),
+    )
+    .unwrap_or_default();
+    let active = CLIENT.api_host_string().await;
+    if !api_hosts.contains(&active) {
+        api_hosts.insert(0, active.to_string());
+    }
+    let default = api_url();
+    if !api_hosts.contains(&default) {
+        api_hosts.push(default);
+    }
+    CLIENT.set_api_hosts(api_hosts).await;
+    CLIENT
+        .set_host_callback(Some(Arc::new(|host| {
+            tokio::spawn(async move {
+                let _ = property::save_property("api".to_owned(), host).await;
+            });
+        })))
+        .await;
+}
+
+pub(crate) async fn init_credentials() {
+    let username = property::load_property(profile::key("username").await)
+        .await
+        .unwrap();
+    let password = secret::load_secret(profile::key("password").await)
+        .await
+        .unwrap();
+    logging::add_secret(password.as_str());
+    CLIENT.set_credentials(username, password).await;
+    let token_key = profile::key("token").await;
+    CLIENT
+        .set_auth_callback(Some(Arc::new(move |change| {
+            auth_event::on_auth_change(token_key.clone(), change)
+        })))
+        .await;
+}
+
+async fn init_retry_policy() {
+    let mut policy = copy_client::RetryPolicy::default();
+    if let Ok(max_attempts) = property::load_property("retry_max_attempts".to_owned())
+        .await
+        .unwrap()
+        .parse::<u32>()
+    {
+        policy.max_attempts = max_attempts;
     }
-    CLIENT.set_api_host(api).await;
+    if let Ok(base_delay) = property::load_property("retry_base_delay_ms".to_owned())
+        .await
+        .unwrap()
+        .parse::<u64>()
+    {
+        policy.base_delay = std::time::Duration::from_millis(base_delay);
+    }
+    if let Ok(max_delay) = property::load_property("retry_max_delay_ms".to_owned())
+        .await
+        .unwrap()
+        .parse::<u64>()
+    {
+        policy.max_delay = std::time::Duration::from_millis(max_delay);
+    }
+    CLIENT.set_retry_policy(policy).await;
 }
 
-async fn init_device() {
-    let mut device = property::load_property("device".to_owned()).await.unwrap();
+// 每个账号使用不同的设备标识
+pub(crate) async fn init_device() {
+    let device_key = profile::key("device").await;
+    let mut device = property::load_property(device_key.clone()).await.unwrap();
     if device.is_empty() {
         device = copy_client::random_device();
-        property::save_property("device".to_owned(), device.clone())
+        property::save_property(device_key, device.clone())
             .await
             .unwrap();
     }
-    let mut device_info = property::load_property("device_info".to_owned()).await.unwrap();
+    let device_info_key = profile::key("device_info").await;
+    let mut device_info = property::load_property(device_info_key.clone())
+        .await
+        .unwrap();
     if device_info.is_empty() {
         device_info = copy_client::random_device();
-        property::save_property("device_info".to_owned(), device_info.clone())
+        property::save_property(device_info_key, device_info.clone())
             .await
             .unwrap();
     }
+    logging::add_secret(device.as_str());
+    logging::add_secret(device_info.as_str());
     CLIENT.set_device(device, device_info).await;
 }
 
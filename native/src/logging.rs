@@ -0,0 +1,285 @@
+use crate::utils::{create_dir_if_not_exists, join_paths};
+use lazy_static::lazy_static;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::Write;
+use std::sync::{Mutex, RwLock};
+
+// 内存中保留的条数
+const RING_CAPACITY: usize = 2000;
+// 单个日志文件的大小, 超过后轮转为 app.log.1 ~ app.log.{MAX_FILES - 1}
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+const MAX_FILES: usize = 3;
+const FILE_NAME: &str = "app.log";
+// 这些字段的值会被替换为 ***
+const SECRET_KEYS: [&str; 7] = [
+    "token",
+    "password",
+    "authorization",
+    "device",
+    "deviceinfo",
+    "device_info",
+    "umstring",
+];
+
+#[derive(Clone, Debug)]
+pub(crate) struct LogEntry {
+    pub(crate) time: i64,
+    pub(crate) level: Level,
+    pub(crate) target: String,
+    pub(crate) message: String,
+}
+
+struct FileSink {
+    dir: String,
+    file: File,
+    size: u64,
+}
+
+struct Logger {
+    ring: Mutex<VecDeque<LogEntry>>,
+    sink: Mutex<Option<FileSink>>,
+    secrets: RwLock<Vec<String>>,
+}
+
+lazy_static! {
+    static ref LOGGER: Logger = Logger {
+        ring: Mutex::new(VecDeque::with_capacity(RING_CAPACITY)),
+        sink: Mutex::new(None),
+        secrets: RwLock::new(vec![]),
+    };
+}
+
+struct LoggerRef;
+
+impl Log for LoggerRef {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let entry = LogEntry {
+            time: chrono::Local::now().timestamp_millis(),
+            level: record.level(),
+            target: record.target().to_owned(),
+            message: redact(record.args().to_string().as_str()),
+        };
+        LOGGER.write_file(&entry);
+        let mut ring = LOGGER.ring.lock().unwrap();
+        if ring.len() >= RING_CAPACITY {
+            ring.pop_front();
+        }
+        ring.push_back(entry);
+    }
+
+    fn flush(&self) {
+        if let Some(sink) = LOGGER.sink.lock().unwrap().as_mut() {
+            let _ = sink.file.flush();
+        }
+    }
+}
+
+impl Logger {
+    fn write_file(&self, entry: &LogEntry) {
+        let mut sink = self.sink.lock().unwrap();
+        if let Some(file_sink) = sink.as_mut() {
+            let line = format!("{}\n", format_entry(entry));
+            if file_sink.file.write_all(line.as_bytes()).is_ok() {
+                file_sink.size += line.len() as u64;
+            }
+            if file_sink.size >= MAX_FILE_SIZE {
+                *sink = rotate(file_sink.dir.as_str()).ok();
+            }
+        }
+    }
+}
+
+// 在init_root中调用, 日志写入 root/logs/app.log
+pub(crate) fn init(dir: &str, level: LevelFilter) {
+    create_dir_if_not_exists(dir);
+    *LOGGER.sink.lock().unwrap() = open(dir).ok();
+    if log::set_logger(&LoggerRef).is_ok() {
+        log::set_max_level(level);
+    }
+}
+
+fn open(dir: &str) -> std::io::Result<FileSink> {
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(join_paths(vec![dir, FILE_NAME]))?;
+    let size = file.metadata()?.len();
+    Ok(FileSink {
+        dir: dir.to_owned(),
+        file,
+        size,
+    })
+}
+
+fn rotate(dir: &str) -> std::io::Result<FileSink> {
+    for i in (1..MAX_FILES).rev() {
+        let from = if i == 1 {
+            join_paths(vec![dir, FILE_NAME])
+        } else {
+            join_paths(vec![dir, format!("{}.{}", FILE_NAME, i - 1).as_str()])
+        };
+        let to = join_paths(vec![dir, format!("{}.{}", FILE_NAME, i).as_str()]);
+        if std::path::Path::new(from.as_str()).exists() {
+            std::fs::rename(from, to)?;
+        }
+    }
+    open(dir)
+}
+
+pub(crate) fn set_level(level: LevelFilter) {
+    log::set_max_level(level);
+}
+
+pub(crate) fn parse_level(level: &str) -> Option<LevelFilter> {
+    level.parse::<LevelFilter>().ok()
+}
+
+// 登录后的token, 密码等没有字段名的值, 出现在日志中时同样替换
+pub(crate) fn add_secret(secret: &str) {
+    if secret.len() < 4 {
+        return;
+    }
+    let mut secrets = LOGGER.secrets.write().unwrap();
+    if !secrets.iter().any(|s| s == secret) {
+        secrets.push(secret.to_owned());
+    }
+}
+
+// 最近的日志, min_level为空时返回全部
+pub(crate) fn fetch(min_level: Option<Level>) -> Vec<LogEntry> {
+    LOGGER
+        .ring
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|entry| min_level.is_none_or(|level| entry.level <= level))
+        .cloned()
+        .collect()
+}
+
+pub(crate) fn clear() -> std::io::Result<()> {
+    LOGGER.ring.lock().unwrap().clear();
+    let mut sink = LOGGER.sink.lock().unwrap();
+    if let Some(file_sink) = sink.as_ref() {
+        let dir = file_sink.dir.clone();
+        for i in 1..MAX_FILES {
+            let path = join_paths(vec![dir.as_str(), format!("{}.{}", FILE_NAME, i).as_str()]);
+            if std::path::Path::new(path.as_str()).exists() {
+                std::fs::remove_file(path)?;
+            }
+        }
+        file_sink.file.set_len(0)?;
+        *sink = Some(open(dir.as_str())?);
+    }
+    Ok(())
+}
+
+pub(crate) fn format_entry(entry: &LogEntry) -> String {
+    let time = chrono::DateTime::from_timestamp_millis(entry.time)
+        .map(|time| {
+            time.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S%.3f")
+                .to_string()
+        })
+        .unwrap_or_default();
+    format!(
+        "{} {:5} {} : {}",
+        time, entry.level, entry.target, entry.message
+    )
+}
+
+fn redact(message: &str) -> String {
+    let mut message = message.to_owned();
+    for key in SECRET_KEYS {
+        message = redact_key(message.as_str(), key);
+    }
+    message = redact_after(message.as_str(), "Token ");
+    for secret in LOGGER.secrets.read().unwrap().iter() {
+        message = message.replace(secret.as_str(), "***");
+    }
+    message
+}
+
+// 替换 "key":"value", "key": "value", key=value 和 key: value 中的value
+fn redact_key(message: &str, key: &str) -> String {
+    let lower = message.to_ascii_lowercase();
+    let mut result = String::with_capacity(message.len());
+    let mut last = 0;
+    let mut search = 0;
+    while let Some(found) = lower[search..].find(key) {
+        let start = search + found;
+        let mut end = start + key.len();
+        search = end;
+        // 只匹配完整的字段名, 例如不匹配 device_info 中的 device
+        let before = lower[..start].chars().next_back();
+        let after = lower[end..].chars().next();
+        if before.is_some_and(|c| c.is_alphanumeric() || c == '_')
+            || after.is_some_and(|c| c.is_alphanumeric() || c == '_')
+        {
+            continue;
+        }
+        let bytes = message.as_bytes();
+        if bytes.get(end) == Some(&b'"') {
+            end += 1;
+        }
+        while bytes.get(end) == Some(&b' ') {
+            end += 1;
+        }
+        if !matches!(bytes.get(end), Some(b':') | Some(b'=')) {
+            continue;
+        }
+        end += 1;
+        while bytes.get(end) == Some(&b' ') {
+            end += 1;
+        }
+        let quoted = bytes.get(end) == Some(&b'"');
+        if quoted {
+            end += 1;
+        }
+        let value_end = message[end..]
+            .find(|c: char| {
+                if quoted {
+                    c == '"'
+                } else {
+                    c.is_whitespace() || matches!(c, '&' | ',' | '}' | ')' | '"')
+                }
+            })
+            .map_or(message.len(), |i| end + i);
+        if value_end == end {
+            continue;
+        }
+        result.push_str(&message[last..end]);
+        result.push_str("***");
+        last = value_end;
+        search = value_end;
+    }
+    result.push_str(&message[last..]);
+    result
+}
+
+fn redact_after(message: &str, prefix: &str) -> String {
+    let mut result = String::with_capacity(message.len());
+    let mut rest = message;
+    while let Some(found) = rest.find(prefix) {
+        let start = found + prefix.len();
+        result.push_str(&rest[..start]);
+        let value_end = rest[start..]
+            .find(|c: char| c.is_whitespace() || c == '"' || c == ',')
+            .map_or(rest.len(), |i| start + i);
+        if value_end > start {
+            result.push_str("***");
+        }
+        rest = &rest[value_end..];
+    }
+    result.push_str(rest);
+    result
+}
@@ -0,0 +1,209 @@
+use crate::copy_client::{ChapterComicInfo, ChapterImage, Group, LastChapter};
+use crate::database::download::{
+    download_comic, download_comic_chapter, download_comic_group, download_comic_page,
+};
+use crate::udto::{
+    UiChapterAndContents, UiChapterData, UiComic, UiComicChapter, UiComicData, UiPageComicChapter,
+};
+use serde::de::DeserializeOwned;
+
+// 从下载的数据构建漫画详情, 没有下载时返回None
+// 图片保留原地址, 显示时按cache_key读取下载的文件
+pub(crate) async fn comic_data(path_word: &str) -> anyhow::Result<Option<UiComicData>> {
+    let Some(comic) = find_comic(path_word).await? else {
+        return Ok(None);
+    };
+    let chapters = fetched_chapters(path_word).await?;
+    let groups = download_comic_group::find_by_comic_path_word(path_word)
+        .await?
+        .into_iter()
+        .map(|group| Group {
+            count: chapters
+                .iter()
+                .filter(|chapter| chapter.group_path_word == group.group_path_word)
+                .count() as i64,
+            name: group.name,
+            path_word: group.group_path_word,
+        })
+        .collect::<Vec<_>>();
+    // 下载的数据没有最新章节, 使用第一个分组中已下载的最后一章
+    let last_chapter = groups
+        .first()
+        .and_then(|group| {
+            chapters
+                .iter()
+                .rev()
+                .find(|chapter| chapter.group_path_word == group.path_word)
+        })
+        .map(|chapter| LastChapter {
+            name: chapter.name.clone(),
+            uuid: chapter.uuid.clone(),
+        })
+        .unwrap_or_default();
+    let popular = comic.popular;
+    Ok(Some(UiComicData {
+        comic: UiComic {
+            alias: comic.alias.clone(),
+            author: json(&comic.author),
+            b_404: comic.b_404,
+            b_hidden: comic.b_hidden,
+            ban: comic.ban,
+            brief: comic.brief.clone(),
+            close_comment: comic.close_comment,
+            close_roast: comic.close_roast,
+            cover: comic.cover.clone(),
+            datetime_updated: comic.datetime_updated.clone(),
+            free_type: json(&comic.free_type),
+            img_type: comic.img_type,
+            last_chapter,
+            name: comic.name.clone(),
+            path_word: comic.path_word.clone(),
+            popular,
+            reclass: json(&comic.reclass),
+            region: json(&comic.region),
+            restrict: json(&comic.restrict),
+            seo_baidu: comic.seo_baidu.clone(),
+            status: json(&comic.status),
+            theme: json(&comic.theme),
+            uuid: comic.uuid.clone(),
+            females: json(&comic.females),
+            males: json(&comic.males),
+        },
+        groups,
+        popular,
+        ..Default::default()
+    }))
+}
+
+pub(crate) async fn comic_chapters(
+    comic_path_word: &str,
+    group_path_word: &str,
+    limit: i64,
+    offset: i64,
+) -> anyhow::Result<Option<UiPageComicChapter>> {
+    if find_comic(comic_path_word).await?.is_none() {
+        return Ok(None);
+    }
+    let chapters = fetched_chapters(comic_path_word)
+        .await?
+        .into_iter()
+        .filter(|chapter| chapter.group_path_word == group_path_word)
+        .collect::<Vec<_>>();
+    let total = chapters.len() as i64;
+    let list = chapters
+        .into_iter()
+        .skip(offset.max(0) as usize)
+        .take(limit.max(0) as usize)
+        .map(|chapter| UiComicChapter {
+            comic_id: chapter.comic_id,
+            comic_path_word: chapter.comic_path_word,
+            count: chapter.count,
+            datetime_created: chapter.datetime_created,
+            group_path_word: chapter.group_path_word,
+            img_type: chapter.img_type,
+            index: chapter.index,
+            name: chapter.name,
+            news: chapter.news,
+            next: chapter.next,
+            ordered: chapter.ordered,
+            prev: chapter.prev,
+            size: chapter.size,
+            type_field: chapter.type_field,
+            uuid: chapter.uuid,
+        })
+        .collect();
+    Ok(Some(UiPageComicChapter {
+        list,
+        total,
+        limit,
+        offset,
+        stale: false,
+    }))
+}
+
+pub(crate) async fn chapter_data(
+    comic_path_word: &str,
+    chapter_uuid: &str,
+) -> anyhow::Result<Option<UiChapterData>> {
+    let Some(comic) = find_comic(comic_path_word).await? else {
+        return Ok(None);
+    };
+    let Some(chapter) = fetched_chapters(comic_path_word)
+        .await?
+        .into_iter()
+        .find(|chapter| chapter.uuid == chapter_uuid)
+    else {
+        return Ok(None);
+    };
+    let pages = download_comic_page::find_by_comic_path_word_and_chapter_uuid(
+        comic_path_word,
+        chapter_uuid,
+    )
+    .await?;
+    if pages.is_empty() {
+        return Ok(None);
+    }
+    // 下载时已经按words排好顺序, image_index就是显示的顺序
+    let words = pages.iter().map(|page| page.image_index as i64).collect();
+    let contents = pages
+        .iter()
+        .map(|page| ChapterImage {
+            url: page.url.clone(),
+        })
+        .collect();
+    Ok(Some(UiChapterData {
+        chapter: UiChapterAndContents {
+            comic_id: chapter.comic_id,
+            comic_path_word: chapter.comic_path_word,
+            contents,
+            count: chapter.count,
+            datetime_created: chapter.datetime_created,
+            group_path_word: chapter.group_path_word,
+            img_type: chapter.img_type,
+            index: chapter.index,
+            is_long: chapter.is_long,
+            name: chapter.name,
+            news: chapter.news,
+            next: chapter.next,
+            ordered: chapter.ordered,
+            prev: chapter.prev,
+            size: chapter.size,
+            type_field: chapter.type_field,
+            uuid: chapter.uuid,
+            words,
+        },
+        comic: ChapterComicInfo {
+            name: comic.name,
+            path_word: comic.path_word,
+            restrict: json(&comic.restrict),
+            uuid: comic.uuid,
+        },
+        ..Default::default()
+    }))
+}
+
+// 正在删除的下载不再使用
+async fn find_comic(path_word: &str) -> anyhow::Result<Option<download_comic::Model>> {
+    Ok(download_comic::find_by_path_word(path_word)
+        .await?
+        .filter(|comic| comic.download_status != download_comic::STATUS_DOWNLOAD_DELETING))
+}
+
+// 已经取得图片列表的章节, 按ordered排序
+async fn fetched_chapters(
+    comic_path_word: &str,
+) -> anyhow::Result<Vec<download_comic_chapter::Model>> {
+    Ok(
+        download_comic_chapter::find_by_comic_path_word(comic_path_word)
+            .await?
+            .into_iter()
+            .filter(|chapter| {
+                chapter.download_status == download_comic_chapter::STATUS_FETCH_SUCCESS
+            })
+            .collect(),
+    )
+}
+
+fn json<T: DeserializeOwned + Default>(value: &str) -> T {
+    serde_json::from_str(value).unwrap_or_default()
+}
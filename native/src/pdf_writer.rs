@@ -0,0 +1,183 @@
+use std::io::Write;
+
+const CATALOG_ID: usize = 1;
+const PAGES_ID: usize = 2;
+const OUTLINES_ID: usize = 3;
+
+// 只支持图片页面和一级书签的PDF写入器, 图片必须是JPEG (DCTDecode)
+pub(crate) struct PdfWriter<W: Write> {
+    out: W,
+    position: usize,
+    offsets: Vec<usize>,
+    pages: Vec<usize>,
+    bookmarks: Vec<(String, usize)>,
+}
+
+impl<W: Write> PdfWriter<W> {
+    pub(crate) fn new(mut out: W) -> std::io::Result<Self> {
+        let header = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n";
+        out.write_all(header)?;
+        Ok(Self {
+            out,
+            position: header.len(),
+            // 0号对象保留, 1~3号对象在finish时写入
+            offsets: vec![0; OUTLINES_ID + 1],
+            pages: vec![],
+            bookmarks: vec![],
+        })
+    }
+
+    fn next_id(&mut self) -> usize {
+        self.offsets.push(0);
+        self.offsets.len() - 1
+    }
+
+    fn write(&mut self, data: &[u8]) -> std::io::Result<()> {
+        self.out.write_all(data)?;
+        self.position += data.len();
+        Ok(())
+    }
+
+    fn object(&mut self, id: usize, dict: &str, stream: Option<&[u8]>) -> std::io::Result<()> {
+        self.offsets[id] = self.position;
+        self.write(format!("{} 0 obj\n{}\n", id, dict).as_bytes())?;
+        if let Some(stream) = stream {
+            self.write(b"stream\n")?;
+            self.write(stream)?;
+            self.write(b"\nendstream\n")?;
+        }
+        self.write(b"endobj\n")
+    }
+
+    // 添加一页, 页面大小与图片的像素大小一致, 返回页码(从0开始)
+    pub(crate) fn add_jpeg_page(
+        &mut self,
+        jpeg: &[u8],
+        width: u32,
+        height: u32,
+        gray: bool,
+    ) -> std::io::Result<usize> {
+        let image_id = self.next_id();
+        self.object(
+            image_id,
+            format!(
+                "<< /Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace {} /BitsPerComponent 8 /Filter /DCTDecode /Length {} >>",
+                width,
+                height,
+                if gray { "/DeviceGray" } else { "/DeviceRGB" },
+                jpeg.len()
+            )
+            .as_str(),
+            Some(jpeg),
+        )?;
+        let content = format!("q {} 0 0 {} 0 0 cm /Im0 Do Q", width, height);
+        let content_id = self.next_id();
+        self.object(
+            content_id,
+            format!("<< /Length {} >>", content.len()).as_str(),
+            Some(content.as_bytes()),
+        )?;
+        let page_id = self.next_id();
+        self.object(
+            page_id,
+            format!(
+                "<< /Type /Page /Parent {} 0 R /MediaBox [0 0 {} {}] /Resources << /XObject << /Im0 {} 0 R >> >> /Contents {} 0 R >>",
+                PAGES_ID, width, height, image_id, content_id
+            )
+            .as_str(),
+            None,
+        )?;
+        self.pages.push(page_id);
+        Ok(self.pages.len() - 1)
+    }
+
+    pub(crate) fn add_bookmark(&mut self, title: &str, page_index: usize) {
+        self.bookmarks.push((title.to_owned(), page_index));
+    }
+
+    pub(crate) fn finish(mut self) -> std::io::Result<W> {
+        let mut outline_ids = vec![];
+        for _ in 0..self.bookmarks.len() {
+            outline_ids.push(self.next_id());
+        }
+        let bookmarks = std::mem::take(&mut self.bookmarks);
+        for (i, (title, page_index)) in bookmarks.iter().enumerate() {
+            let mut dict = format!(
+                "<< /Title {} /Parent {} 0 R /Dest [{} 0 R /Fit]",
+                pdf_text(title),
+                OUTLINES_ID,
+                self.pages[*page_index]
+            );
+            if i > 0 {
+                dict.push_str(format!(" /Prev {} 0 R", outline_ids[i - 1]).as_str());
+            }
+            if i + 1 < outline_ids.len() {
+                dict.push_str(format!(" /Next {} 0 R", outline_ids[i + 1]).as_str());
+            }
+            dict.push_str(" >>");
+            self.object(outline_ids[i], dict.as_str(), None)?;
+        }
+        let outlines = match (outline_ids.first(), outline_ids.last()) {
+            (Some(first), Some(last)) => format!(
+                "<< /Type /Outlines /First {} 0 R /Last {} 0 R /Count {} >>",
+                first,
+                last,
+                outline_ids.len()
+            ),
+            _ => "<< /Type /Outlines /Count 0 >>".to_owned(),
+        };
+        self.object(OUTLINES_ID, outlines.as_str(), None)?;
+        let kids = self
+            .pages
+            .iter()
+            .map(|id| format!("{} 0 R", id))
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.object(
+            PAGES_ID,
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                kids,
+                self.pages.len()
+            )
+            .as_str(),
+            None,
+        )?;
+        self.object(
+            CATALOG_ID,
+            format!(
+                "<< /Type /Catalog /Pages {} 0 R /Outlines {} 0 R /PageMode /UseOutlines >>",
+                PAGES_ID, OUTLINES_ID
+            )
+            .as_str(),
+            None,
+        )?;
+        let xref_position = self.position;
+        let mut xref = format!("xref\n0 {}\n0000000000 65535 f \n", self.offsets.len());
+        for offset in &self.offsets[1..] {
+            xref.push_str(format!("{:010} 00000 n \n", offset).as_str());
+        }
+        xref.push_str(
+            format!(
+                "trailer\n<< /Size {} /Root {} 0 R >>\nstartxref\n{}\n%%EOF\n",
+                self.offsets.len(),
+                CATALOG_ID,
+                xref_position
+            )
+            .as_str(),
+        );
+        self.write(xref.as_bytes())?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+// 书签标题使用带BOM的UTF-16BE十六进制字符串, 以支持中文
+fn pdf_text(text: &str) -> String {
+    let mut hex = String::from("<FEFF");
+    for unit in text.encode_utf16() {
+        hex.push_str(format!("{:04X}", unit).as_str());
+    }
+    hex.push('>');
+    hex
+}
@@ -0,0 +1,117 @@
+use crate::database::active::comic_subscription;
+use crate::database::cache::web_cache;
+use crate::database::properties::{property, secret};
+use lazy_static::lazy_static;
+use tokio::sync::Mutex;
+
+// 默认账号的数据不加后缀, 兼容之前的单账号版本
+pub(crate) const DEFAULT_PROFILE: &str = "default";
+// 这些值每个账号单独保存
+pub(crate) const PROFILE_KEYS: [&str; 6] = [
+    "token",
+    "username",
+    "password",
+    "device",
+    "device_info",
+    "browse_sync_time",
+];
+
+lazy_static! {
+    static ref ACTIVE_PROFILE: Mutex<String> = Mutex::new(DEFAULT_PROFILE.to_owned());
+}
+
+pub(crate) async fn load() {
+    let active = property::load_property("active_profile".to_owned())
+        .await
+        .unwrap();
+    if !active.is_empty() && list().await.unwrap().contains(&active) {
+        *ACTIVE_PROFILE.lock().await = active;
+    }
+}
+
+pub(crate) async fn active() -> String {
+    ACTIVE_PROFILE.lock().await.clone()
+}
+
+// property或secret的key, 例如 token@work
+pub(crate) async fn key(k: &str) -> String {
+    scoped_key(k, active().await.as_str())
+}
+
+fn scoped_key(k: &str, profile: &str) -> String {
+    if profile == DEFAULT_PROFILE {
+        k.to_owned()
+    } else {
+        format!("{}@{}", k, profile)
+    }
+}
+
+// 与账号相关的web_cache的key, 例如 @work$COMIC_COLLECT$...
+pub(crate) async fn cache_key(key: &str) -> String {
+    let profile = active().await;
+    if profile == DEFAULT_PROFILE {
+        key.to_owned()
+    } else {
+        format!("@{}${}", profile, key)
+    }
+}
+
+pub(crate) async fn list() -> anyhow::Result<Vec<String>> {
+    let mut profiles: Vec<String> = serde_json::from_str(
+        property::load_property("profiles".to_owned())
+            .await?
+            .as_str(),
+    )
+    .unwrap_or_default();
+    if !profiles.iter().any(|p| p == DEFAULT_PROFILE) {
+        profiles.insert(0, DEFAULT_PROFILE.to_owned());
+    }
+    Ok(profiles)
+}
+
+async fn save_list(profiles: &[String]) -> anyhow::Result<()> {
+    property::save_property("profiles".to_owned(), serde_json::to_string(profiles)?).await
+}
+
+pub(crate) async fn add(name: String) -> anyhow::Result<()> {
+    if name.is_empty() || name.contains(['@', '$', '%']) {
+        return Err(anyhow::anyhow!("invalid profile name"));
+    }
+    let mut profiles = list().await?;
+    if profiles.contains(&name) {
+        return Err(anyhow::anyhow!("profile already exists"));
+    }
+    profiles.push(name);
+    save_list(&profiles).await
+}
+
+// 删除账号保存的值和缓存, 之后添加同名的账号不会读到之前账号的数据
+pub(crate) async fn remove(name: String) -> anyhow::Result<()> {
+    if name == DEFAULT_PROFILE || name == active().await {
+        return Err(anyhow::anyhow!("can not remove default or active profile"));
+    }
+    let mut profiles = list().await?;
+    profiles.retain(|p| p != &name);
+    save_list(&profiles).await?;
+    for k in PROFILE_KEYS {
+        let k = scoped_key(k, name.as_str());
+        if property::SECRET_KEYS.contains(&property::base_key(k.as_str())) {
+            secret::delete_secret(k).await?;
+        } else {
+            property::delete_property(k).await?;
+        }
+    }
+    web_cache::clean_web_cache_by_prefix(format!("@{}$", name).as_str()).await?;
+    comic_subscription::delete_by_profile(name.as_str()).await?;
+    Ok(())
+}
+
+// 切换后需要调用 crate::init_device 和 crate::init_credentials 重新设置CLIENT
+pub(crate) async fn set_active(name: String) -> anyhow::Result<()> {
+    if !list().await?.contains(&name) {
+        return Err(anyhow::anyhow!("profile not found"));
+    }
+    property::save_property("active_profile".to_owned(), name.clone()).await?;
+    *ACTIVE_PROFILE.lock().await = name;
+    Ok(())
+}
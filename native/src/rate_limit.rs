@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+const MIN_BACKOFF: Duration = Duration::from_secs(2);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+    backoff: Duration,
+    blocked_until: Option<Instant>,
+}
+
+impl Bucket {
+    fn new(rate: f64) -> Self {
+        Self {
+            tokens: rate,
+            last_refill: Instant::now(),
+            backoff: Duration::ZERO,
+            blocked_until: None,
+        }
+    }
+}
+
+// 按域名的令牌桶限速, rate为每秒请求数, 0为不限速
+// 服务器返回429/5xx时进入退避, 退避时间翻倍直到MAX_BACKOFF, 成功后减半
+pub(crate) struct HostRateLimiter {
+    rate: Mutex<f64>,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl HostRateLimiter {
+    pub(crate) fn new(rate: f64) -> Self {
+        Self {
+            rate: Mutex::new(rate),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) async fn rate(&self) -> f64 {
+        *self.rate.lock().await
+    }
+
+    pub(crate) async fn set_rate(&self, rate: f64) {
+        *self.rate.lock().await = rate;
+        self.buckets.lock().await.clear();
+    }
+
+    pub(crate) async fn acquire(&self, url: &str) {
+        let host = host_of(url);
+        loop {
+            let rate = self.rate().await;
+            let mut buckets = self.buckets.lock().await;
+            let bucket = buckets
+                .entry(host.clone())
+                .or_insert_with(|| Bucket::new(rate));
+            let now = Instant::now();
+            if let Some(blocked_until) = bucket.blocked_until {
+                if blocked_until > now {
+                    drop(buckets);
+                    tokio::time::sleep(blocked_until - now).await;
+                    continue;
+                }
+                bucket.blocked_until = None;
+            }
+            if rate <= 0.0 {
+                return;
+            }
+            let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+            bucket.tokens = (bucket.tokens + elapsed * rate).min(rate.max(1.0));
+            bucket.last_refill = now;
+            if bucket.tokens >= 1.0 {
+                bucket.tokens -= 1.0;
+                return;
+            }
+            let wait = Duration::from_secs_f64((1.0 - bucket.tokens) / rate);
+            drop(buckets);
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    pub(crate) async fn on_success(&self, url: &str) {
+        let mut buckets = self.buckets.lock().await;
+        if let Some(bucket) = buckets.get_mut(&host_of(url)) {
+            bucket.backoff /= 2;
+            if bucket.backoff < MIN_BACKOFF {
+                bucket.backoff = Duration::ZERO;
+            }
+        }
+    }
+
+    pub(crate) async fn on_throttled(&self, url: &str) {
+        let rate = self.rate().await;
+        let mut buckets = self.buckets.lock().await;
+        let bucket = buckets
+            .entry(host_of(url))
+            .or_insert_with(|| Bucket::new(rate));
+        bucket.backoff = (bucket.backoff * 2).clamp(MIN_BACKOFF, MAX_BACKOFF);
+        bucket.blocked_until = Some(Instant::now() + bucket.backoff);
+    }
+}
+
+fn host_of(url: &str) -> String {
+    url::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_owned))
+        .unwrap_or_default()
+}
+
+pub(crate) fn is_throttled_status(status: u16) -> bool {
+    status == 429 || (500..600).contains(&status)
+}
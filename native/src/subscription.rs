@@ -0,0 +1,121 @@
+use crate::database::active::{comic_subscription, comic_view_log, local_collect};
+use crate::database::cache::web_cache;
+use crate::{profile, CLIENT};
+use futures_util::StreamExt;
+use lazy_static::lazy_static;
+use std::collections::{HashMap, HashSet};
+use tokio::sync::Mutex;
+
+const PAGE_SIZE: u64 = 50;
+// 同时查询的漫画数量
+const CONCURRENCY: usize = 4;
+
+lazy_static! {
+    static ref REFRESH_LOCK: Mutex<()> = Mutex::new(());
+}
+
+// 重新查询收藏的漫画, 返回这次发现有新章节的漫画数量
+pub(crate) async fn refresh_subscriptions() -> anyhow::Result<u64> {
+    let _lock = REFRESH_LOCK.lock().await;
+    // 刷新过程中切换账号也写入开始时的账号
+    let profile = profile::active().await;
+    let path_words = followed_comics().await?;
+    comic_subscription::delete_not_in(profile.as_str(), path_words.clone()).await?;
+    let known: HashMap<String, comic_subscription::Model> =
+        comic_subscription::load_all(profile.as_str())
+            .await?
+            .into_iter()
+            .map(|model| (model.comic_path_word.clone(), model))
+            .collect();
+    let results = futures_util::stream::iter(path_words.into_iter().map(|path_word| {
+        let old = known.get(&path_word).cloned();
+        let profile = profile.clone();
+        async move {
+            let result = refresh_comic(profile, path_word.as_str(), old).await;
+            if let Err(e) = &result {
+                log::warn!("refresh subscription {} error: {:?}", path_word, e);
+            }
+            result
+        }
+    }))
+    .buffer_unordered(CONCURRENCY)
+    .collect::<Vec<_>>()
+    .await;
+    Ok(results
+        .into_iter()
+        .filter(|result| matches!(result, Ok(true)))
+        .count() as u64)
+}
+
+// 本地收藏和账号收藏的漫画, 账号收藏读取失败时返回错误, 避免删除跟踪记录
+async fn followed_comics() -> anyhow::Result<Vec<String>> {
+    let mut added = HashSet::new();
+    let mut path_words = vec![];
+    let count = local_collect::count().await?;
+    for model in local_collect::load_local_collects("append_time", 0, count).await? {
+        if added.insert(model.path_word.clone()) {
+            path_words.push(model.path_word);
+        }
+    }
+    if CLIENT.get_token().await.is_empty() {
+        return Ok(path_words);
+    }
+    let mut offset = 0;
+    loop {
+        let page = CLIENT
+            .collected_comics(1, "-datetime_updated", offset, PAGE_SIZE)
+            .await?;
+        let size = page.list.len() as u64;
+        for collected in page.list {
+            if added.insert(collected.comic.path_word.clone()) {
+                path_words.push(collected.comic.path_word);
+            }
+        }
+        offset += size;
+        if size == 0 || offset >= page.total as u64 {
+            break;
+        }
+    }
+    Ok(path_words)
+}
+
+async fn refresh_comic(
+    profile: String,
+    path_word: &str,
+    old: Option<comic_subscription::Model>,
+) -> anyhow::Result<bool> {
+    let comic = CLIENT.comic(path_word).await?.comic;
+    let now = chrono::Local::now().timestamp_millis();
+    // 已经读到最新章节的不算作更新
+    let read = comic_view_log::view_log_by_comic_path_word(path_word.to_owned())
+        .await?
+        .is_some_and(|view_log| view_log.chapter_uuid == comic.last_chapter.uuid);
+    let changed = old
+        .as_ref()
+        .is_some_and(|old| old.last_chapter_uuid != comic.last_chapter.uuid);
+    let (has_update, update_time) = match &old {
+        // 第一次跟踪, 只记录当前的最新章节
+        None => (false, 0),
+        Some(_) if changed && !read => (true, now),
+        Some(old) => (old.has_update && !read, old.update_time),
+    };
+    if changed {
+        web_cache::clean_web_cache_by_key(format!("COMIC${path_word}").as_str()).await?;
+        web_cache::clean_web_cache_by_key(format!("COMIC_ALL_CHAPTERS${path_word}").as_str())
+            .await?;
+    }
+    comic_subscription::save(comic_subscription::Model {
+        profile,
+        comic_path_word: path_word.to_owned(),
+        comic_name: comic.name,
+        comic_cover: comic.cover,
+        last_chapter_uuid: comic.last_chapter.uuid,
+        last_chapter_name: comic.last_chapter.name,
+        datetime_updated: comic.datetime_updated,
+        check_time: now,
+        has_update,
+        update_time,
+    })
+    .await?;
+    Ok(changed && !read)
+}
@@ -1,10 +1,12 @@
 use crate::copy_client::{
-    Author, Browse, ChapterAndContents, ChapterComicInfo, ChapterData, ChapterImage, ClassifyItem,
-    CollectedComic, Comic, ComicChapter, ComicData, ComicInExplore, ComicInList, ComicInSearch,
-    Group, LastChapter, MemberInfo, Page, RankItem, RecommendItem, RegisterResult,
-    SexualOrientation, Tag,
+    Author, Browse, BrowseComic, BrowseComicComic, ChapterAndContents, ChapterComicInfo,
+    ChapterData, ChapterImage, ClassifyItem, CollectedComic, Comic, ComicChapter, ComicData,
+    ComicInExplore, ComicInList, ComicInSearch, Comment, Group, GroupChapters, LastChapter,
+    MemberInfo, Page, RankItem, RecommendItem, RegisterResult, Roast, SexualOrientation, Tag,
+};
+use crate::database::active::{
+    author_work, comic_subscription, comic_view_log, followed_author, local_collect,
 };
-use crate::database::active::comic_view_log;
 use crate::database::download::download_comic_page::Model;
 use crate::utils::join_paths;
 use crate::{downloading, get_image_cache_dir};
@@ -47,6 +49,111 @@ impl From<comic_view_log::Model> for UiViewLog {
     }
 }
 
+#[napi(object)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UiFollowedAuthor {
+    pub author_path_word: String,
+    pub author_name: String,
+    pub follow_time: i64,
+    pub last_check_time: i64,
+}
+
+impl From<followed_author::Model> for UiFollowedAuthor {
+    fn from(model: followed_author::Model) -> Self {
+        Self {
+            author_path_word: model.author_path_word,
+            author_name: model.author_name,
+            follow_time: model.follow_time,
+            last_check_time: model.last_check_time,
+        }
+    }
+}
+
+#[napi(object)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UiPageUiFollowedAuthor {
+    pub list: Vec<UiFollowedAuthor>,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+#[napi(object)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UiAuthorWork {
+    pub author_path_word: String,
+    pub comic_path_word: String,
+    pub comic_name: String,
+    pub comic_authors: String,
+    pub comic_cover: String,
+    pub datetime_updated: String,
+    pub found_time: i64,
+    pub seen: bool,
+}
+
+impl From<author_work::Model> for UiAuthorWork {
+    fn from(model: author_work::Model) -> Self {
+        Self {
+            author_path_word: model.author_path_word,
+            comic_path_word: model.comic_path_word,
+            comic_name: model.comic_name,
+            comic_authors: model.comic_authors,
+            comic_cover: model.comic_cover,
+            datetime_updated: model.datetime_updated,
+            found_time: model.found_time,
+            seen: model.seen,
+        }
+    }
+}
+
+#[napi(object)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UiPageUiAuthorWork {
+    pub list: Vec<UiAuthorWork>,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+#[napi(object)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UiComicSubscription {
+    pub comic_path_word: String,
+    pub comic_name: String,
+    pub comic_cover: String,
+    pub last_chapter_uuid: String,
+    pub last_chapter_name: String,
+    pub datetime_updated: String,
+    pub check_time: i64,
+    pub has_update: bool,
+    pub update_time: i64,
+}
+
+impl From<comic_subscription::Model> for UiComicSubscription {
+    fn from(model: comic_subscription::Model) -> Self {
+        Self {
+            comic_path_word: model.comic_path_word,
+            comic_name: model.comic_name,
+            comic_cover: model.comic_cover,
+            last_chapter_uuid: model.last_chapter_uuid,
+            last_chapter_name: model.last_chapter_name,
+            datetime_updated: model.datetime_updated,
+            check_time: model.check_time,
+            has_update: model.has_update,
+            update_time: model.update_time,
+        }
+    }
+}
+
+#[napi(object)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UiPageUiComicSubscription {
+    pub list: Vec<UiComicSubscription>,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
 //////////////////////////////////////
 
 #[napi(object)]
@@ -56,6 +163,9 @@ pub struct UiPageRankItem {
     pub total: i64,
     pub limit: i64,
     pub offset: i64,
+    // 返回的是已经过期的缓存
+    #[serde(default)]
+    pub stale: bool,
 }
 
 impl From<Page<RankItem>> for UiPageRankItem {
@@ -65,6 +175,7 @@ impl From<Page<RankItem>> for UiPageRankItem {
             total: page.total,
             limit: page.limit,
             offset: page.offset,
+            stale: false,
         }
     }
 }
@@ -146,6 +257,9 @@ pub struct UiComicData {
     pub is_mobile_bind: bool,
     pub is_vip: bool,
     pub popular: i64,
+    // 返回的是已经过期的缓存
+    #[serde(default)]
+    pub stale: bool,
 }
 
 impl From<ComicData> for UiComicData {
@@ -158,6 +272,7 @@ impl From<ComicData> for UiComicData {
             is_mobile_bind: comic.is_mobile_bind,
             is_vip: comic.is_vip,
             popular: comic.popular,
+            stale: false,
         }
     }
 }
@@ -306,6 +421,9 @@ pub struct UiPageComicChapter {
     pub total: i64,
     pub limit: i64,
     pub offset: i64,
+    // 返回的是已经过期的缓存
+    #[serde(default)]
+    pub stale: bool,
 }
 
 impl From<Page<ComicChapter>> for UiPageComicChapter {
@@ -319,6 +437,7 @@ impl From<Page<ComicChapter>> for UiPageComicChapter {
             total: page.total,
             limit: page.limit,
             offset: page.offset,
+            stale: false,
         }
     }
 }
@@ -366,6 +485,33 @@ impl From<ComicChapter> for UiComicChapter {
 }
 #[napi(object)]
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UiGroupChapters {
+    pub group: Group,
+    pub chapters: Vec<UiComicChapter>,
+}
+
+impl From<GroupChapters> for UiGroupChapters {
+    fn from(group: GroupChapters) -> Self {
+        Self {
+            group: group.group,
+            chapters: group
+                .chapters
+                .into_iter()
+                .map(UiComicChapter::from)
+                .collect(),
+        }
+    }
+}
+
+// last_chapter_uuid与漫画的最新章节不同时缓存失效
+#[napi(object)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UiComicAllChapters {
+    pub last_chapter_uuid: String,
+    pub groups: Vec<UiGroupChapters>,
+}
+#[napi(object)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct UiComicQuery {
     pub browse: Option<Browse>,
     pub collect: Option<i64>,
@@ -557,6 +703,9 @@ pub struct UiPageComicInExplore {
     pub total: i64,
     pub limit: i64,
     pub offset: i64,
+    // 返回的是已经过期的缓存
+    #[serde(default)]
+    pub stale: bool,
 }
 
 impl From<Page<ComicInExplore>> for UiPageComicInExplore {
@@ -570,6 +719,7 @@ impl From<Page<ComicInExplore>> for UiPageComicInExplore {
             total: page.total,
             limit: page.limit,
             offset: page.offset,
+            stale: false,
         }
     }
 }
@@ -788,6 +938,78 @@ pub struct UiLocalCollect {
     pub uuid: String,
     pub append_time: i64,
 }
+
+impl From<local_collect::Model> for UiLocalCollect {
+    fn from(model: local_collect::Model) -> Self {
+        Self {
+            path_word: model.path_word,
+            alias: model.alias,
+            author: model.author,
+            b_404: model.b_404,
+            b_hidden: model.b_hidden,
+            ban: model.ban,
+            brief: model.brief,
+            close_comment: model.close_comment,
+            close_roast: model.close_roast,
+            cover: model.cover,
+            datetime_updated: model.datetime_updated,
+            females: model.females,
+            free_type: model.free_type,
+            img_type: model.img_type,
+            males: model.males,
+            name: model.name,
+            popular: model.popular,
+            reclass: model.reclass,
+            region: model.region,
+            restrict: model.restrict,
+            seo_baidu: model.seo_baidu,
+            status: model.status,
+            theme: model.theme,
+            uuid: model.uuid,
+            append_time: model.append_time,
+        }
+    }
+}
+
+impl From<UiLocalCollect> for local_collect::Model {
+    fn from(model: UiLocalCollect) -> Self {
+        Self {
+            path_word: model.path_word,
+            alias: model.alias,
+            author: model.author,
+            b_404: model.b_404,
+            b_hidden: model.b_hidden,
+            ban: model.ban,
+            brief: model.brief,
+            close_comment: model.close_comment,
+            close_roast: model.close_roast,
+            cover: model.cover,
+            datetime_updated: model.datetime_updated,
+            females: model.females,
+            free_type: model.free_type,
+            img_type: model.img_type,
+            males: model.males,
+            name: model.name,
+            popular: model.popular,
+            reclass: model.reclass,
+            region: model.region,
+            restrict: model.restrict,
+            seo_baidu: model.seo_baidu,
+            status: model.status,
+            theme: model.theme,
+            uuid: model.uuid,
+            append_time: model.append_time,
+        }
+    }
+}
+#[napi(object)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UiPageUiLocalCollect {
+    pub list: Vec<UiLocalCollect>,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
 #[napi(object)]
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct UiDownloadComicGroup {
@@ -904,3 +1126,285 @@ impl From<Page<CollectedComic>> for UiPageCollectedComic {
     }
 }
 
+#[napi(object)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UiPageComment {
+    pub list: Vec<UiComment>,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+impl From<Page<Comment>> for UiPageComment {
+    fn from(page: Page<Comment>) -> Self {
+        Self {
+            list: page.list.into_iter().map(UiComment::from).collect(),
+            total: page.total,
+            limit: page.limit,
+            offset: page.offset,
+        }
+    }
+}
+#[napi(object)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UiComment {
+    pub id: i64,
+    pub create_at: String,
+    pub user_id: String,
+    pub user_name: String,
+    pub user_avatar: String,
+    pub comment: String,
+    pub count: i64,
+    pub parent_id: Option<i64>,
+    pub parent_user_id: Option<String>,
+    pub parent_user_name: Option<String>,
+}
+
+impl From<Comment> for UiComment {
+    fn from(comment: Comment) -> Self {
+        Self {
+            id: comment.id,
+            create_at: comment.create_at,
+            user_id: comment.user_id,
+            user_name: comment.user_name,
+            user_avatar: comment.user_avatar,
+            comment: comment.comment,
+            count: comment.count,
+            parent_id: comment.parent_id,
+            parent_user_id: comment.parent_user_id,
+            parent_user_name: comment.parent_user_name,
+        }
+    }
+}
+#[napi(object)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UiPageRoast {
+    pub list: Vec<UiRoast>,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+impl From<Page<Roast>> for UiPageRoast {
+    fn from(page: Page<Roast>) -> Self {
+        Self {
+            list: page.list.into_iter().map(UiRoast::from).collect(),
+            total: page.total,
+            limit: page.limit,
+            offset: page.offset,
+        }
+    }
+}
+#[napi(object)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UiRoast {
+    pub id: i64,
+    pub create_at: String,
+    pub user_id: String,
+    pub user_name: String,
+    pub user_avatar: String,
+    pub comment: String,
+}
+
+impl From<Roast> for UiRoast {
+    fn from(roast: Roast) -> Self {
+        Self {
+            id: roast.id,
+            create_at: roast.create_at,
+            user_id: roast.user_id,
+            user_name: roast.user_name,
+            user_avatar: roast.user_avatar,
+            comment: roast.comment,
+        }
+    }
+}
+#[napi(object)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UiPageBrowseComic {
+    pub list: Vec<UiBrowseComic>,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+impl From<Page<BrowseComic>> for UiPageBrowseComic {
+    fn from(page: Page<BrowseComic>) -> Self {
+        Self {
+            list: page.list.into_iter().map(UiBrowseComic::from).collect(),
+            total: page.total,
+            limit: page.limit,
+            offset: page.offset,
+        }
+    }
+}
+#[napi(object)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UiBrowseComic {
+    pub id: i64,
+    pub last_chapter_id: String,
+    pub last_chapter_name: String,
+    pub comic: UiBrowseComicComic,
+}
+
+impl From<BrowseComic> for UiBrowseComic {
+    fn from(browse: BrowseComic) -> Self {
+        Self {
+            id: browse.id,
+            last_chapter_id: browse.last_chapter_id,
+            last_chapter_name: browse.last_chapter_name,
+            comic: UiBrowseComicComic::from(browse.comic),
+        }
+    }
+}
+#[napi(object)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UiBrowseComicComic {
+    pub uuid: String,
+    pub b_display: bool,
+    pub name: String,
+    pub path_word: String,
+    pub females: Vec<SexualOrientation>,
+    pub males: Vec<SexualOrientation>,
+    pub author: Vec<Author>,
+    pub theme: Vec<Tag>,
+    pub cover: String,
+    pub status: i64,
+    pub popular: i64,
+    pub datetime_updated: String,
+    pub last_chapter_id: String,
+    pub last_chapter_name: String,
+}
+
+impl From<BrowseComicComic> for UiBrowseComicComic {
+    fn from(comic: BrowseComicComic) -> Self {
+        Self {
+            uuid: comic.uuid,
+            b_display: comic.b_display,
+            name: comic.name,
+            path_word: comic.path_word,
+            females: comic.females,
+            males: comic.males,
+            author: comic.author,
+            theme: comic.theme,
+            cover: comic.cover,
+            status: comic.status,
+            popular: comic.popular,
+            datetime_updated: comic.datetime_updated,
+            last_chapter_id: comic.last_chapter_id,
+            last_chapter_name: comic.last_chapter_name,
+        }
+    }
+}
+#[napi(object)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UiDownloadEvent {
+    // COMIC_STARTED, CHAPTER_FETCHED, CHAPTER_FAILED, PAGE_DOWNLOADED, PAGE_FAILED,
+    // COMIC_COMPLETED, COMIC_FAILED, PAUSED, RESUMED
+    pub event_type: String,
+    pub comic_path_word: String,
+    pub chapter_uuid: Option<String>,
+    pub image_index: Option<i32>,
+    pub bytes: i64,
+    pub image_count: i64,
+    pub image_count_success: i64,
+}
+#[napi(object)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UiDownloadComicExport {
+    pub comic_path_word: String,
+    pub export_to: String,
+    pub exports_type: String,
+    pub export_status: i64,
+    pub message: String,
+    pub export_time: i64,
+}
+
+impl From<crate::database::download::download_comic_export::Model> for UiDownloadComicExport {
+    fn from(model: crate::database::download::download_comic_export::Model) -> Self {
+        Self {
+            comic_path_word: model.comic_path_word,
+            export_to: model.export_to,
+            exports_type: model.exports_type,
+            export_status: model.export_status,
+            message: model.message,
+            export_time: model.export_time,
+        }
+    }
+}
+#[napi(object)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UiRetryPolicy {
+    pub max_attempts: i32,
+    pub base_delay_ms: i64,
+    pub max_delay_ms: i64,
+}
+
+impl From<&crate::copy_client::RetryPolicy> for UiRetryPolicy {
+    fn from(policy: &crate::copy_client::RetryPolicy) -> Self {
+        Self {
+            max_attempts: policy.max_attempts as i32,
+            base_delay_ms: policy.base_delay.as_millis() as i64,
+            max_delay_ms: policy.max_delay.as_millis() as i64,
+        }
+    }
+}
+
+#[napi(object)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UiRetryStats {
+    pub api_retries: i64,
+    pub image_retries: i64,
+    pub api_exhausted: i64,
+    pub image_exhausted: i64,
+}
+
+impl From<crate::copy_client::RetryStats> for UiRetryStats {
+    fn from(stats: crate::copy_client::RetryStats) -> Self {
+        Self {
+            api_retries: stats.api_retries as i64,
+            image_retries: stats.image_retries as i64,
+            api_exhausted: stats.api_exhausted as i64,
+            image_exhausted: stats.image_exhausted as i64,
+        }
+    }
+}
+
+#[napi(object)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UiApiHostProbe {
+    pub host: String,
+    pub available: bool,
+    pub latency_ms: i64,
+    pub message: String,
+}
+
+impl From<crate::copy_client::HostProbe> for UiApiHostProbe {
+    fn from(probe: crate::copy_client::HostProbe) -> Self {
+        Self {
+            host: probe.host,
+            available: probe.available,
+            latency_ms: probe.latency.as_millis() as i64,
+            message: probe.message,
+        }
+    }
+}
+
+#[napi(object)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UiLogEntry {
+    pub time: i64,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+}
+
+impl From<crate::logging::LogEntry> for UiLogEntry {
+    fn from(entry: crate::logging::LogEntry) -> Self {
+        Self {
+            time: entry.time,
+            level: entry.level.to_string(),
+            target: entry.target,
+            message: entry.message,
+        }
+    }
+}